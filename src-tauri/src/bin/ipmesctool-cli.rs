@@ -0,0 +1,369 @@
+//! 无界面的命令行工具，与 GUI 共用 Motor 后端，用于 CI 测试台和 SSH 远程调试
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use ipmesctool_lib::command::{MotorConfigCommand, MotorRunCommand, MotorState};
+use ipmesctool_lib::config_parser::MotorConfig;
+use ipmesctool_lib::device_log::LogLevel;
+use ipmesctool_lib::error::MotorError;
+use ipmesctool_lib::events::{EventSink, MotorEvent};
+use ipmesctool_lib::feedback_parser::FeedbackValue;
+use ipmesctool_lib::firmware_image::FirmwareImage;
+use ipmesctool_lib::flasher::flash_firmware;
+use ipmesctool_lib::motor::{Motor, MotorFeedbackState};
+use ipmesctool_lib::probe::{probe_port, DEFAULT_BAUD_RATES, PROBE_TIMEOUT};
+use ipmesctool_lib::serial::{SerialDevice, SerialPortDescriptor};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{sleep, timeout, Duration, Instant};
+
+#[derive(Parser)]
+#[command(name = "ipmesctool-cli", version, about = "Headless tool for IPM ESC boards")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct PortArgs {
+    /// 串口名称，如 COM3 或 /dev/ttyACM0
+    #[arg(short, long)]
+    port: String,
+    #[arg(short, long, default_value_t = 115200)]
+    baud_rate: u32,
+    /// 不协商二进制协议，始终使用文本协议
+    #[arg(long)]
+    text_protocol: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 列出可用串口
+    Ports,
+    /// 依次尝试常用波特率，检测串口上是否有电调应答
+    Probe {
+        /// 串口名称，如 COM3 或 /dev/ttyACM0
+        #[arg(short, long)]
+        port: String,
+    },
+    /// 读取配置并以 JSON 输出
+    DumpConfig {
+        #[command(flatten)]
+        port: PortArgs,
+        /// 输出文件，默认输出到标准输出
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// 下发 dump-config 格式的配置文件
+    ApplyProfile {
+        #[command(flatten)]
+        port: PortArgs,
+        profile: PathBuf,
+        /// 下发后保存到 flash
+        #[arg(long)]
+        save: bool,
+    },
+    /// 执行校准
+    Calibrate {
+        #[command(flatten)]
+        port: PortArgs,
+        /// 校准完成后保存到 flash
+        #[arg(long)]
+        save: bool,
+    },
+    /// 以给定速度运行，结束或 Ctrl-C 后停机
+    SetSpeed {
+        #[command(flatten)]
+        port: PortArgs,
+        #[arg(allow_negative_numbers = true)]
+        speed: f32,
+        /// 运行时长（秒），默认一直运行到 Ctrl-C
+        #[arg(short, long, value_parser = parse_duration)]
+        duration: Option<Duration>,
+    },
+    /// 记录反馈数据为 CSV
+    Record {
+        #[command(flatten)]
+        port: PortArgs,
+        #[arg(short, long, value_enum)]
+        feedback: FeedbackKind,
+        /// 记录时长（秒）
+        #[arg(short, long, default_value = "5", value_parser = parse_duration)]
+        duration: Duration,
+        /// 输出文件，默认输出到标准输出
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// 通过 bootloader 烧录固件（.hex 或 .bin）
+    Flash {
+        #[command(flatten)]
+        port: PortArgs,
+        image: PathBuf,
+    },
+}
+
+impl Command {
+    fn port(&self) -> Option<&PortArgs> {
+        match self {
+            Command::Ports | Command::Probe { .. } => None,
+            Command::DumpConfig { port, .. }
+            | Command::ApplyProfile { port, .. }
+            | Command::Calibrate { port, .. }
+            | Command::SetSpeed { port, .. }
+            | Command::Record { port, .. }
+            | Command::Flash { port, .. } => Some(port),
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FeedbackKind {
+    Speed,
+    Position,
+    Current,
+    Udc,
+}
+
+impl FeedbackKind {
+    fn state(self) -> MotorFeedbackState {
+        match self {
+            FeedbackKind::Speed => MotorFeedbackState::Speed,
+            FeedbackKind::Position => MotorFeedbackState::Position,
+            FeedbackKind::Current => MotorFeedbackState::Current,
+            FeedbackKind::Udc => MotorFeedbackState::Udc,
+        }
+    }
+}
+
+/// 解析以秒为单位的时长，拒绝负数、NaN 和溢出
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let secs: f32 = s.parse().map_err(|e| format!("{e}"))?;
+    Duration::try_from_secs_f32(secs).map_err(|e| format!("{e}"))
+}
+
+type CliResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// 命令行下只输出校准、烧录进度、下位机告警和断线提示
+#[derive(Debug)]
+struct CliEventSink;
+
+impl EventSink for CliEventSink {
+    fn emit(&self, event: MotorEvent) {
+        match event {
+            MotorEvent::CalibrationProgress(progress) => eprintln!("calibration: {:?}", progress),
+            MotorEvent::FlashProgress(progress) => eprintln!("flash: {:?} {}/{}", progress.stage, progress.written, progress.total),
+            MotorEvent::DeviceLog(log) if log.level != LogLevel::Info => eprintln!("device: {}", log.message),
+            MotorEvent::Disconnected => eprintln!("device disconnected"),
+            _ => {}
+        }
+    }
+}
+
+async fn open_motor(args: &PortArgs) -> CliResult<Arc<Motor>> {
+    let serial = SerialDevice::new(args.port.clone(), args.baud_rate);
+    serial.connect().await?;
+    // 上次异常退出时下位机可能停留在二进制协议
+    serial.reset_remote_protocol().await?;
+    let motor = Motor::new(serial, Arc::new(CliEventSink));
+    motor.start_parse_feedback_loop().await;
+    if let Err(e) = start_session(&motor, args).await {
+        close_motor(motor).await;
+        return Err(e);
+    }
+    Ok(motor)
+}
+
+async fn start_session(motor: &Arc<Motor>, args: &PortArgs) -> CliResult<()> {
+    let firmware = motor.query_firmware().await?;
+    eprintln!("firmware {} {}", firmware.version, firmware.build);
+    if !args.text_protocol {
+        eprintln!("protocol {}", motor.negotiate_protocol().await?);
+    }
+    // 命令行异常退出时由下位机超时停机
+    motor.start_keepalive(None).await;
+    Ok(())
+}
+
+/// 切换回文本协议后断开，出错时也要调用
+async fn close_motor(motor: Arc<Motor>) {
+    if let Err(e) = motor.close().await {
+        eprintln!("close: {e}");
+    }
+}
+
+/// 写入文件或标准输出
+fn write_output(output: &Option<PathBuf>, text: &str) -> CliResult<()> {
+    match output {
+        Some(path) => std::fs::write(path, text)?,
+        None => std::io::stdout().write_all(text.as_bytes())?,
+    }
+    Ok(())
+}
+
+async fn dump_config(motor: &Arc<Motor>, output: &Option<PathBuf>) -> CliResult<()> {
+    let config = motor.load_config().await?;
+    let json = serde_json::to_string_pretty(&config)?;
+    write_output(output, &(json + "\n"))
+}
+
+async fn apply_profile(motor: &Arc<Motor>, profile: &Path, save: bool) -> CliResult<()> {
+    let text = std::fs::read_to_string(profile)?;
+    let config: MotorConfig = serde_json::from_str(&text)?;
+    for cmd in MotorConfigCommand::from_config(&config) {
+        motor.send_config_command(&cmd).await?;
+        // 给下位机留出处理时间
+        sleep(Duration::from_millis(50)).await;
+    }
+    if save {
+        motor.save_config().await?;
+    }
+    eprintln!("profile applied{}", if save { " and saved" } else { "" });
+    Ok(())
+}
+
+async fn calibrate(motor: &Arc<Motor>, save: bool) -> CliResult<()> {
+    motor.calibration().await?;
+    if save {
+        motor.save_config().await?;
+    }
+    Ok(())
+}
+
+async fn set_speed(motor: &Arc<Motor>, speed: f32, duration: Option<Duration>) -> CliResult<()> {
+    let mut rx = motor.feedback_tx.subscribe();
+    motor.send_running_command(&MotorRunCommand::SetSpeed(speed)).await?;
+    let print_feedback = async {
+        loop {
+            match rx.recv().await {
+                Ok(sample) => {
+                    if let FeedbackValue::Speed(v) = sample.value {
+                        println!("{v}");
+                    }
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return,
+            }
+        }
+    };
+    let wait = async {
+        match duration {
+            Some(duration) => sleep(duration).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        _ = print_feedback => {}
+        _ = wait => {}
+    }
+    motor.send_running_command(&MotorRunCommand::Stop).await?;
+    Ok(())
+}
+
+async fn record(motor: &Arc<Motor>, kind: FeedbackKind, duration: Duration, output: &Option<PathBuf>) -> CliResult<()> {
+    let mut rx = motor.feedback_tx.subscribe();
+    motor.set_feedback(kind.state()).await?;
+    let start = Instant::now();
+    let mut csv = match kind {
+        FeedbackKind::Current => String::from("time_ms,ia,ib,ic\n"),
+        _ => String::from("time_ms,value\n"),
+    };
+    let result = timeout(duration, async {
+        loop {
+            let sample = match rx.recv().await {
+                Ok(sample) => sample,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return Err::<(), _>(MotorError::Disconnected),
+            };
+            let t = start.elapsed().as_secs_f64() * 1000f64;
+            match sample.value {
+                FeedbackValue::Current(ia, ib, ic) => csv.push_str(&format!("{t:.3},{ia},{ib},{ic}\n")),
+                FeedbackValue::Speed(v) | FeedbackValue::Position(v) | FeedbackValue::Udc(v) => csv.push_str(&format!("{t:.3},{v}\n")),
+            }
+        }
+    }).await;
+    motor.set_feedback(MotorFeedbackState::None).await?;
+    if let Ok(Err(e)) = result {
+        return Err(e.into());
+    }
+    write_output(output, &csv)
+}
+
+async fn flash(motor: &Arc<Motor>, image: &Path) -> CliResult<()> {
+    let image = FirmwareImage::load(image)?;
+    eprintln!("image {} bytes at {:08x}, crc32 {:08x}", image.data.len(), image.base_address, image.crc32());
+    flash_firmware(&motor.serial, &image, motor.events.as_ref()).await?;
+    Ok(())
+}
+
+async fn probe(port: &str) -> CliResult<()> {
+    let result = probe_port(port, DEFAULT_BAUD_RATES, PROBE_TIMEOUT).await?;
+    match result.baud_rate {
+        Some(baud_rate) => {
+            println!("{baud_rate}");
+            Ok(())
+        }
+        None => Err(format!("no ESC answered on {port}, tried {:?}", result.tried).into()),
+    }
+}
+
+async fn run(cli: Cli) -> CliResult<()> {
+    if let Command::Probe { port } = &cli.command {
+        return probe(port).await;
+    }
+    let Some(port) = cli.command.port() else {
+        for p in SerialPortDescriptor::list()? {
+            let usb = match (p.vid, p.pid) {
+                (Some(vid), Some(pid)) => format!("{vid:04x}:{pid:04x}"),
+                _ => p.port_type.clone(),
+            };
+            let product = p.product.as_deref().unwrap_or("-");
+            let tag = if p.is_esc { "\t[esc]" } else if p.usb_uart { "\t[usb-uart]" } else { "" };
+            println!("{}\t{}\t{}{}", p.port_name, usb, product, tag);
+        }
+        return Ok(());
+    };
+    let motor = open_motor(port).await?;
+    let result = tokio::select! {
+        result = execute(&motor, &cli.command) => result,
+        _ = tokio::signal::ctrl_c() => interrupt(&motor, &cli.command).await,
+    };
+    close_motor(motor).await;
+    result
+}
+
+async fn execute(motor: &Arc<Motor>, command: &Command) -> CliResult<()> {
+    match command {
+        Command::Ports | Command::Probe { .. } => Ok(()),
+        Command::DumpConfig { output, .. } => dump_config(motor, output).await,
+        Command::ApplyProfile { profile, save, .. } => apply_profile(motor, profile, *save).await,
+        Command::Calibrate { save, .. } => calibrate(motor, *save).await,
+        Command::SetSpeed { speed, duration, .. } => set_speed(motor, *speed, *duration).await,
+        Command::Record { feedback, duration, output, .. } => record(motor, *feedback, *duration, output).await,
+        Command::Flash { image, .. } => flash(motor, image).await,
+    }
+}
+
+/// Ctrl-C 中断时先停机，随后照常关闭以恢复文本协议
+async fn interrupt(motor: &Arc<Motor>, command: &Command) -> CliResult<()> {
+    if matches!(motor.state(), MotorState::DebugRun | MotorState::Run) {
+        motor.send_running_command(&MotorRunCommand::Stop).await?;
+    }
+    // 不限时长的 set-speed 本就以 Ctrl-C 结束
+    match command {
+        Command::SetSpeed { duration: None, .. } => Ok(()),
+        _ => Err("interrupted".into()),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
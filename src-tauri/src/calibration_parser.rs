@@ -1,6 +1,12 @@
+use crate::error::MotorError;
+use crate::link_stats::LinkStats;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{timeout, Duration};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[derive(PartialEq)]
 pub enum ParserState {
     Idle,
@@ -15,6 +21,12 @@ pub enum ParserState {
 
 pub struct CalibrationParser(pub ParserState);
 
+impl Default for CalibrationParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CalibrationParser {
     pub fn new() -> Self {
         Self(ParserState::Idle)
@@ -89,4 +101,35 @@ impl CalibrationParser {
     pub fn is_done(&self) -> bool {
         self.0 == ParserState::Done
     }
-}
\ No newline at end of file
+}
+
+/// 跟踪校准过程直到完成，需在发送 calibration 之前订阅
+pub async fn receive_calibration(rx: &mut broadcast::Receiver<String>, stats: &LinkStats, duration: Duration, mut on_progress: impl FnMut(&ParserState)) -> Result<(), MotorError> {
+    let mut parser = CalibrationParser::new();
+    let result = timeout(duration, async {
+        loop {
+            let line = match rx.recv().await {
+                Ok(line) => line,
+                // 校准期间反馈仍可能很密集，丢弃的行不影响后续进度
+                Err(RecvError::Lagged(n)) => {
+                    stats.lagged.fetch_add(n, Ordering::Relaxed);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            match parser.parse(&line) {
+                Ok(_) => {
+                    on_progress(&parser.0);
+                    if parser.is_done() {
+                        return Ok(());
+                    }
+                }
+                Err(e) => {
+                    return Err(MotorError::CalibrationError(e))
+                }
+            }
+        }
+        Err(MotorError::SerialError("recv error".into()))
+    }).await;
+    result.unwrap_or(Err(MotorError::Timeout))
+}
@@ -1,3 +1,5 @@
+use crate::config_parser::MotorConfig;
+use crate::frame::Protocol;
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString};
 
@@ -10,6 +12,13 @@ pub enum MotorState {
     Fault,
 }
 
+/// 会改变上位机所记录状态的指令，只能通过对应的专用命令发送
+pub const STATEFUL_COMMANDS: &[&str] = &[
+    "get_speed", "get_position", "get_current", "get_udc", "get_none",
+    "set_speed", "set_position", "stop", "calibration", "clear_fault", "keepalive", "protocol",
+    "bootloader",
+];
+
 pub enum MotorFeedbackCommand {
     GetSpeed,
     GetPosition,
@@ -17,6 +26,12 @@ pub enum MotorFeedbackCommand {
     GetUdc,
     GetConfig,
     GetNone,
+    GetVersion,
+    GetState,
+    /// 下位机在超时时间内没有收到下一次 keepalive 则停机
+    Keepalive { timeout_ms: u64 },
+    /// 切换通信协议，下位机以切换前的协议应答
+    SetProtocol(Protocol),
 }
 impl MotorFeedbackCommand {
     pub fn to_string(&self) -> String {
@@ -27,6 +42,10 @@ impl MotorFeedbackCommand {
             MotorFeedbackCommand::GetUdc => "get_udc\r\n".into(),
             MotorFeedbackCommand::GetConfig => "get_config\r\n".into(),
             MotorFeedbackCommand::GetNone => "get_none\r\n".into(),
+            MotorFeedbackCommand::GetVersion => "get_version\r\n".into(),
+            MotorFeedbackCommand::GetState => "get_state\r\n".into(),
+            MotorFeedbackCommand::Keepalive { timeout_ms } => format!("keepalive {timeout_ms}\r\n"),
+            MotorFeedbackCommand::SetProtocol(protocol) => format!("protocol {protocol}\r\n"),
         }
     }
 }
@@ -67,6 +86,51 @@ pub enum MotorConfigCommand {
 }
 
 impl MotorConfigCommand {
+    /// 将完整配置展开为逐项配置指令
+    pub fn from_config(config: &MotorConfig) -> Vec<Self> {
+        vec![
+            MotorConfigCommand::ConfigId(config.id),
+            MotorConfigCommand::ConfigUdc(config.udc),
+            MotorConfigCommand::ConfigPositionPid {
+                kp: config.position_pid.kp,
+                ki: config.position_pid.ki,
+                kd: config.position_pid.kd,
+                output_max: config.position_pid.output_max,
+            },
+            MotorConfigCommand::ConfigSpeedPi {
+                kp: config.speed_pi.kp,
+                ki: config.speed_pi.ki,
+                output_max: config.speed_pi.output_max,
+            },
+            MotorConfigCommand::ConfigCurrentPi {
+                id_kp: config.current_id_pi.kp,
+                id_ki: config.current_id_pi.ki,
+                iq_kp: config.current_iq_pi.kp,
+                iq_ki: config.current_iq_pi.ki,
+            },
+            MotorConfigCommand::ConfigIdqFilter(config.fc),
+            MotorConfigCommand::ConfigEncoder {
+                pole_pairs: config.encoder_config.pole_pairs,
+                encoder_direct: config.encoder_config.encoder_direction as i8,
+                encoder_offset: config.encoder_config.encoder_offset,
+                encoder_type: config.encoder_config.encoder_type.to_string(),
+            },
+        ]
+    }
+
+    /// 下位机指令名，用于判断固件是否支持
+    pub fn name(&self) -> &'static str {
+        match self {
+            MotorConfigCommand::ConfigPositionPid { .. } => "config_position_pid",
+            MotorConfigCommand::ConfigSpeedPi { .. } => "config_speed_pi",
+            MotorConfigCommand::ConfigCurrentPi { .. } => "config_current_pi",
+            MotorConfigCommand::ConfigIdqFilter(_) => "config_idq_filter",
+            MotorConfigCommand::ConfigEncoder { .. } => "config_encoder",
+            MotorConfigCommand::ConfigId(_) => "config_id",
+            MotorConfigCommand::ConfigUdc(_) => "config_udc",
+        }
+    }
+
     pub fn to_string(&self, state: &MotorState) -> Option<String> {
         match self {
             MotorConfigCommand::ConfigPositionPid { kp, ki, kd, output_max } => {
@@ -155,4 +219,21 @@ impl MotorCalibrationCommand {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+pub enum MotorFaultCommand {
+    ClearFault,
+}
+
+impl MotorFaultCommand {
+    pub fn to_string(&self, state: &MotorState) -> Option<String> {
+        match self {
+            MotorFaultCommand::ClearFault => {
+                match state {
+                    MotorState::Fault => Some("clear_fault\r\n".into()),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
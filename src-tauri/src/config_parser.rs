@@ -1,6 +1,12 @@
+use crate::error::MotorError;
+use crate::link_stats::LinkStats;
 use scan_fmt::scan_fmt;
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString};
+use std::sync::atomic::Ordering;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{timeout, Duration};
 
 #[derive(Debug, Default)]
 struct PositionPID {
@@ -198,6 +204,34 @@ impl ConfigParser {
     }
 }
 
+/// 从串口消息中读取一份完整配置，需在发送 get_config 之前订阅
+pub async fn receive_config(rx: &mut broadcast::Receiver<String>, stats: &LinkStats, duration: Duration) -> Result<MotorConfig, MotorError> {
+    let mut config_parser = ConfigParser::default();
+    let mut section = String::new();
+    let result = timeout(duration, async {
+        loop {
+            let line = match rx.recv().await {
+                Ok(line) => line,
+                // 丢失的行会导致配置不完整，由解析结果报错
+                Err(RecvError::Lagged(n)) => {
+                    stats.lagged.fetch_add(n, Ordering::Relaxed);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            config_parser.parse_line(&line, &mut section);
+
+            if config_parser.is_complete() {
+                break;
+            }
+        }
+    }).await;
+    match result {
+        Ok(_) => config_parser.try_into_motor_config().map_err(|e| MotorError::ParseError(e.to_string())),
+        Err(_) => Err(MotorError::Timeout),
+    }
+}
+
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PositionPIDConfig {
@@ -0,0 +1,40 @@
+use crate::fault_parser::parse_fault;
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+/// 下位机主动发出的消息（告警、故障等），请求的应答不算在内
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceLog {
+    pub timestamp: u64, // 毫秒
+    pub level: LogLevel,
+    pub message: String,
+}
+
+impl DeviceLog {
+    pub fn new(message: &str) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        Self { timestamp, level: classify(message), message: message.to_string() }
+    }
+}
+
+/// 按关键字判断消息等级
+fn classify(message: &str) -> LogLevel {
+    let lower = message.to_ascii_lowercase();
+    if parse_fault(message).is_some() || ["fault", "error", "err ", "fail"].iter().any(|k| lower.contains(k)) {
+        LogLevel::Error
+    } else if lower.contains("warn") {
+        LogLevel::Warn
+    } else {
+        LogLevel::Info
+    }
+}
@@ -1,21 +1,135 @@
+use crate::command::MotorState;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
 use thiserror::Error;
 
+/// 错误分类，前端按分类决定处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ErrorCategory {
+    NotConnected,
+    InvalidState,
+    InvalidArgument,
+    Timeout,
+    DeviceRejected,
+    Parse,
+    Io,
+}
+
 #[derive(Error, Debug)]
 pub enum MotorError {
+    #[error("motor is not connected")]
+    NotConnected,
+    #[error("motor is already connected in port {port}")]
+    AlreadyConnected { port: String },
     #[error("serial port error: {0}")]
     SerialError(String),
+    #[error("io error: {0}")]
+    IoError(String),
     #[error("parse error: {0}")]
     ParseError(String),
-    #[error("invalid state: {0}")]
-    InvalidState(String),
+    #[error("invalid state: cannot {action} in {state} state")]
+    InvalidState { state: MotorState, action: String },
+    #[error("busy: {0}")]
+    Busy(String),
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("safety limit: {0}")]
+    SafetyLimit(String),
     #[error("calibration error: {0}")]
     CalibrationError(String),
+    #[error("firmware update failed: {0}")]
+    FlashError(String),
+    #[error("emergency stop is active")]
+    EmergencyStopped,
     #[error("fault detected: {0}")]
     FaultDetected(String),
+    #[error("{feature} is not supported by firmware {firmware}")]
+    Unsupported { feature: String, firmware: String },
     #[error("timeout")]
     Timeout,
-    #[error("emit event error: {0}")]
-    TauriEmitError(tauri::Error),
     #[error("disconnected")]
     Disconnected,
-}
\ No newline at end of file
+}
+
+impl MotorError {
+    pub fn invalid_state(state: MotorState, action: impl Into<String>) -> Self {
+        MotorError::InvalidState { state, action: action.into() }
+    }
+
+    /// 稳定的错误码，前端据此匹配，不要修改已有的值
+    pub fn code(&self) -> &'static str {
+        match self {
+            MotorError::NotConnected => "NOT_CONNECTED",
+            MotorError::AlreadyConnected { .. } => "ALREADY_CONNECTED",
+            MotorError::SerialError(_) => "SERIAL_ERROR",
+            MotorError::IoError(_) => "IO_ERROR",
+            MotorError::ParseError(_) => "PARSE_ERROR",
+            MotorError::InvalidState { .. } => "INVALID_STATE",
+            MotorError::Busy(_) => "BUSY",
+            MotorError::InvalidArgument(_) => "INVALID_ARGUMENT",
+            MotorError::SafetyLimit(_) => "SAFETY_LIMIT",
+            MotorError::CalibrationError(_) => "CALIBRATION_FAILED",
+            MotorError::FlashError(_) => "FLASH_FAILED",
+            MotorError::EmergencyStopped => "EMERGENCY_STOPPED",
+            MotorError::FaultDetected(_) => "FAULT_DETECTED",
+            MotorError::Unsupported { .. } => "UNSUPPORTED",
+            MotorError::Timeout => "TIMEOUT",
+            MotorError::Disconnected => "DISCONNECTED",
+        }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            MotorError::NotConnected | MotorError::Disconnected => ErrorCategory::NotConnected,
+            MotorError::AlreadyConnected { .. } | MotorError::InvalidState { .. } | MotorError::Busy(_) | MotorError::EmergencyStopped => ErrorCategory::InvalidState,
+            MotorError::InvalidArgument(_) | MotorError::SafetyLimit(_) => ErrorCategory::InvalidArgument,
+            MotorError::Timeout => ErrorCategory::Timeout,
+            MotorError::CalibrationError(_) | MotorError::FlashError(_) | MotorError::FaultDetected(_) | MotorError::Unsupported { .. } => ErrorCategory::DeviceRejected,
+            MotorError::ParseError(_) => ErrorCategory::Parse,
+            MotorError::SerialError(_) | MotorError::IoError(_) => ErrorCategory::Io,
+        }
+    }
+
+    /// 附加的上下文字段
+    pub fn context(&self) -> Value {
+        match self {
+            MotorError::NotConnected | MotorError::EmergencyStopped | MotorError::Timeout | MotorError::Disconnected => Value::Null,
+            MotorError::AlreadyConnected { port } => json!({ "port": port }),
+            MotorError::InvalidState { state, action } => json!({ "state": state, "action": action }),
+            MotorError::Unsupported { feature, firmware } => json!({ "feature": feature, "firmware": firmware }),
+            MotorError::SerialError(detail)
+            | MotorError::IoError(detail)
+            | MotorError::ParseError(detail)
+            | MotorError::Busy(detail)
+            | MotorError::InvalidArgument(detail)
+            | MotorError::SafetyLimit(detail)
+            | MotorError::CalibrationError(detail)
+            | MotorError::FlashError(detail)
+            | MotorError::FaultDetected(detail) => json!({ "detail": detail }),
+        }
+    }
+}
+
+impl Serialize for MotorError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("MotorError", 4)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("category", &self.category())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("context", &self.context())?;
+        s.end()
+    }
+}
+
+impl From<std::io::Error> for MotorError {
+    fn from(e: std::io::Error) -> Self {
+        MotorError::IoError(e.to_string())
+    }
+}
+
+impl From<tokio_serial::Error> for MotorError {
+    fn from(e: tokio_serial::Error) -> Self {
+        MotorError::SerialError(e.to_string())
+    }
+}
@@ -0,0 +1,102 @@
+use crate::command::{MotorRunCommand, MotorState};
+use crate::motor::query_serial_state;
+use crate::serial::SerialDevice;
+use log::warn;
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+
+/// 单个串口的急停结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceStopResult {
+    pub port_name: String,
+    /// stop 已写入串口
+    pub sent: bool,
+    pub error: Option<String>,
+    /// 急停后查询到的下位机状态，固件不支持查询或超时时为空
+    pub state: Option<MotorState>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EmergencyStopReport {
+    pub devices: Vec<DeviceStopResult>,
+}
+
+impl DeviceStopResult {
+    /// 已写入 stop 且查询到的状态不是运行中，未能查询到状态时不算确认
+    pub fn is_confirmed(&self) -> bool {
+        self.sent && self.state.is_some() && !is_running(self.state)
+    }
+}
+
+impl EmergencyStopReport {
+    /// 所有串口都已确认停机
+    pub fn is_confirmed(&self) -> bool {
+        self.devices.iter().all(DeviceStopResult::is_confirmed)
+    }
+}
+
+fn is_running(state: Option<MotorState>) -> bool {
+    matches!(state, Some(MotorState::Run | MotorState::DebugRun))
+}
+
+/// 向所有打开的串口直接写入 stop，再查询状态确认，仍在运行时补发一次
+pub async fn emergency_stop_all() -> EmergencyStopReport {
+    let line = MotorRunCommand::Stop.to_string(&MotorState::Stop).unwrap();
+    let devices = SerialDevice::open_devices();
+    // 先全部写入，再逐个确认，避免确认过程拖慢其他设备的停机
+    let mut sent = Vec::with_capacity(devices.len());
+    for device in &devices {
+        sent.push(device.emergency_stop(&line).await);
+    }
+
+    let handles: Vec<_> = devices.into_iter().zip(sent).map(|(device, sent)| {
+        let line = line.clone();
+        tokio::spawn(async move {
+            let mut result = DeviceStopResult {
+                port_name: device.port_name.clone(),
+                sent: sent.is_ok(),
+                error: sent.err(),
+                state: None,
+            };
+            // 写入失败时串口可能已卡死，查询同样会阻塞
+            if result.sent {
+                for _ in 0..2 {
+                    match query_serial_state(&device).await {
+                        Ok(state) => result.state = Some(state),
+                        Err(e) => {
+                            warn!("Failed to verify emergency stop on {}: {e}", device.port_name);
+                            break;
+                        }
+                    }
+                    if !is_running(result.state) {
+                        break;
+                    }
+                    if let Err(e) = device.emergency_stop(&line).await {
+                        result.error = Some(e);
+                    }
+                }
+            }
+            result
+        })
+    }).collect();
+
+    let mut report = EmergencyStopReport::default();
+    for handle in handles {
+        if let Ok(result) = handle.await {
+            report.devices.push(result);
+        }
+    }
+    report
+}
+
+/// 解除所有串口的急停锁存
+pub fn clear_emergency_stop() {
+    for device in SerialDevice::open_devices() {
+        device.emergency_stopped.store(false, Ordering::Relaxed);
+    }
+}
+
+/// 是否有串口处于急停锁存
+pub fn is_emergency_stopped() -> bool {
+    SerialDevice::open_devices().iter().any(|d| d.emergency_stopped.load(Ordering::Relaxed))
+}
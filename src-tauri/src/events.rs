@@ -0,0 +1,97 @@
+use crate::calibration_parser::ParserState;
+use crate::command::MotorState;
+use crate::device_log::DeviceLog;
+use crate::fault_parser::FaultRecord;
+use crate::feedback_parser::FeedbackValue;
+use crate::flasher::FlashProgress;
+use crate::link_stats::LinkReport;
+use crate::motor::Timestamped;
+use crate::safety::SafetyViolation;
+use crate::sweep::SweepProgress;
+use crate::trajectory::TrackingReport;
+use log::error;
+use std::fmt::Debug;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+/// 电机向外部发出的事件
+#[derive(Debug, Clone)]
+pub enum MotorEvent {
+    StateChanged(MotorState),
+    Feedback(Timestamped<FeedbackValue>),
+    SerialReceived(String),
+    SerialSent(String),
+    DeviceLog(DeviceLog),
+    CalibrationProgress(ParserState),
+    Fault(FaultRecord),
+    SafetyViolation(SafetyViolation),
+    Disconnected,
+    /// 连接中断，正在等待自动重连
+    ConnectionLost,
+    /// 自动重连成功，参数为新的端口名
+    Reconnected(String),
+    SetpointStreamStopped,
+    /// 前端心跳超时，已自动停机
+    WatchdogTripped,
+    SweepProgress(SweepProgress),
+    TrajectoryReport(TrackingReport),
+    FlashProgress(FlashProgress),
+    /// 周期上报的链路状况
+    LinkStats(LinkReport),
+}
+
+/// 事件输出，GUI 下转发给前端，测试和命令行下可替换为其他实现
+pub trait EventSink: Send + Sync + Debug {
+    fn emit(&self, event: MotorEvent);
+}
+
+/// 以 Tauri 事件的形式转发给前端
+#[derive(Debug, Clone)]
+pub struct TauriEventSink(pub AppHandle);
+
+impl EventSink for TauriEventSink {
+    fn emit(&self, event: MotorEvent) {
+        let app = &self.0;
+        let result = match event {
+            MotorEvent::StateChanged(state) => app.emit("motor-state-change", state),
+            MotorEvent::Feedback(sample) => app.emit("motor_feedback_update", sample),
+            MotorEvent::SerialReceived(line) => app.emit("serial-received", line),
+            MotorEvent::SerialSent(line) => app.emit("serial-sent", line),
+            MotorEvent::DeviceLog(log) => app.emit("device-log", log),
+            MotorEvent::CalibrationProgress(state) => app.emit("calibration-state", state),
+            MotorEvent::Fault(record) => app.emit("motor-fault", record),
+            MotorEvent::SafetyViolation(violation) => app.emit("safety-violation", violation),
+            MotorEvent::Disconnected => app.emit("motor-disconnected", ()),
+            MotorEvent::ConnectionLost => app.emit("motor-connection-lost", ()),
+            MotorEvent::Reconnected(port) => app.emit("motor-reconnected", port),
+            MotorEvent::SetpointStreamStopped => app.emit("setpoint-stream-stopped", ()),
+            MotorEvent::WatchdogTripped => app.emit("watchdog-tripped", ()),
+            MotorEvent::SweepProgress(progress) => app.emit("sweep-progress", progress),
+            MotorEvent::TrajectoryReport(report) => app.emit("trajectory-report", report),
+            MotorEvent::FlashProgress(progress) => app.emit("flash-progress", progress),
+            MotorEvent::LinkStats(report) => app.emit("link-stats", report),
+        };
+        if let Err(e) = result {
+            error!("Tauri emit error {e}");
+        }
+    }
+}
+
+/// 将事件保存在内存中，用于测试
+#[derive(Debug, Default)]
+pub struct MemoryEventSink {
+    events: Mutex<Vec<MotorEvent>>,
+}
+
+impl MemoryEventSink {
+    /// 取出并清空已记录的事件
+    pub fn take(&self) -> Vec<MotorEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+}
+
+impl EventSink for MemoryEventSink {
+    fn emit(&self, event: MotorEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::command::MotorState;
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+use strum_macros::Display;
+
+/// 下位机上报的故障类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize)]
+pub enum FaultCode {
+    Overcurrent,
+    Overvoltage,
+    Undervoltage,
+    EncoderError,
+    OverTemperature,
+    Unknown,
+}
+
+impl FaultCode {
+    /// 按单词匹配故障名，下划线、连字符与空格等价
+    fn from_keyword(text: &str) -> Option<Self> {
+        let text = text.to_ascii_lowercase().replace(['_', '-'], " ");
+        let mut words = text.split(|c: char| c.is_whitespace() || c == ':' || c == ',').filter(|w| !w.is_empty());
+        let code = match (words.next()?, words.next()) {
+            ("overcurrent" | "oc", _) | ("over", Some("current")) => FaultCode::Overcurrent,
+            ("overvoltage" | "ov", _) | ("over", Some("voltage")) => FaultCode::Overvoltage,
+            ("undervoltage" | "uv", _) | ("under", Some("voltage")) => FaultCode::Undervoltage,
+            ("overtemperature" | "overtemp" | "ot", _) | ("over", Some("temperature" | "temp")) => FaultCode::OverTemperature,
+            ("encoder" | "enc", _) => FaultCode::EncoderError,
+            _ => return None,
+        };
+        Some(code)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            FaultCode::Overcurrent => "phase current exceeded the limit",
+            FaultCode::Overvoltage => "bus voltage too high",
+            FaultCode::Undervoltage => "bus voltage too low",
+            FaultCode::EncoderError => "encoder read failed",
+            FaultCode::OverTemperature => "power stage over temperature",
+            FaultCode::Unknown => "unknown fault",
+        }
+    }
+}
+
+/// 故障记录
+#[derive(Debug, Clone, Serialize)]
+pub struct FaultRecord {
+    pub timestamp: u64, // 毫秒
+    pub code: FaultCode,
+    pub description: String,
+    /// 下位机原始消息
+    pub raw: String,
+    /// 故障发生前的电机状态
+    pub previous_state: MotorState,
+}
+
+impl FaultRecord {
+    pub fn new(code: FaultCode, raw: &str, previous_state: MotorState) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        Self { timestamp, code, description: code.description().to_string(), raw: raw.to_string(), previous_state }
+    }
+}
+
+/// 解析故障消息，支持 `fault: <code> [detail]` 以及直接以故障名开头的消息，
+/// 如 `overcurrent`、`undervoltage 9.8V`、`encoder error`
+pub fn parse_fault(line: &str) -> Option<FaultCode> {
+    let lower = line.trim().to_ascii_lowercase();
+    if let Some(rest) = lower.strip_prefix("fault") {
+        let rest = rest.trim_start_matches([':', ' ']);
+        // clear_fault 的应答
+        if rest.starts_with("clear") || rest.starts_with("none") {
+            return None;
+        }
+        return Some(FaultCode::from_keyword(rest).unwrap_or(FaultCode::Unknown));
+    }
+    let code = FaultCode::from_keyword(&lower)?;
+    // 不带 fault 前缀时，编码器消息（如配置中的 encoder_type）需要明确包含错误字样，缩写不识别
+    let explicit = match code {
+        FaultCode::EncoderError => lower.contains("error") || lower.contains("fail"),
+        _ => !["oc", "ov", "uv", "ot"].iter().any(|abbr| lower.split_whitespace().next() == Some(abbr)),
+    };
+    explicit.then_some(code)
+}
@@ -0,0 +1,61 @@
+use crate::command::MotorState;
+use crate::motor::MotorFeedbackState;
+use scan_fmt::scan_fmt;
+use serde::Serialize;
+
+/// 单条反馈数据
+///
+/// 序列化为 untagged，保持与前端 `motor_feedback_update` 事件中 `value` 字段的格式一致
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum FeedbackValue {
+    Speed(f32),
+    Position(f32),
+    Current(f32, f32, f32),
+    Udc(f32),
+}
+
+impl FeedbackValue {
+    /// 前端使用的类型名
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            FeedbackValue::Speed(_) => "speed",
+            FeedbackValue::Position(_) => "position",
+            FeedbackValue::Current(..) => "iabc",
+            FeedbackValue::Udc(_) => "udc",
+        }
+    }
+}
+
+/// 按当前反馈类型解析一行串口消息
+pub fn parse_feedback(feedback: MotorFeedbackState, line: &str) -> Option<FeedbackValue> {
+    match feedback {
+        MotorFeedbackState::Speed => scan_fmt!(line, "speed: {}", f32).ok().map(FeedbackValue::Speed),
+        MotorFeedbackState::Position => scan_fmt!(line, "position: {}", f32).ok().map(FeedbackValue::Position),
+        MotorFeedbackState::Current => scan_fmt!(line, "iabc:{},{},{}", f32, f32, f32)
+            .ok()
+            .map(|(ia, ib, ic)| FeedbackValue::Current(ia, ib, ic)),
+        MotorFeedbackState::Udc => scan_fmt!(line, "udc: {}", f32).ok().map(FeedbackValue::Udc),
+        MotorFeedbackState::None => None,
+    }
+}
+
+/// 是否为任意类型的反馈数据，切换反馈类型后仍可能收到上一类型的数据
+pub fn is_feedback_line(line: &str) -> bool {
+    [MotorFeedbackState::Speed, MotorFeedbackState::Position, MotorFeedbackState::Current, MotorFeedbackState::Udc]
+        .into_iter()
+        .any(|feedback| parse_feedback(feedback, line).is_some())
+}
+
+/// 以反馈数据的前缀开头，用于统计解析失败
+pub fn looks_like_feedback(line: &str) -> bool {
+    ["speed:", "position:", "iabc:", "udc:"].iter().any(|prefix| line.starts_with(prefix))
+}
+
+/// 解析 `get_state` 的应答，如 `state: DebugRun`
+pub fn parse_state(line: &str) -> Option<MotorState> {
+    let state = line.trim().strip_prefix("state:")?.trim();
+    [MotorState::Stop, MotorState::DebugRun, MotorState::Run, MotorState::Test, MotorState::Fault]
+        .into_iter()
+        .find(|s| s.to_string().eq_ignore_ascii_case(state))
+}
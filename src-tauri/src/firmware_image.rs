@@ -0,0 +1,75 @@
+use crate::error::MotorError;
+use crc::{Crc, CRC_32_ISO_HDLC};
+use ihex::{Reader, Record};
+use std::path::Path;
+
+/// 二进制镜像默认的烧录地址（STM32 Flash 起始地址）
+pub const DEFAULT_BASE_ADDRESS: u32 = 0x0800_0000;
+
+/// HEX 中地址空洞填充值，与擦除后的 Flash 一致
+const FILL_BYTE: u8 = 0xff;
+
+/// 整段空洞超过该值认为 HEX 文件有误
+const MAX_IMAGE_SIZE: usize = 4 * 1024 * 1024;
+
+pub const IMAGE_CRC: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+/// 待烧录的连续固件镜像
+#[derive(Debug, Clone)]
+pub struct FirmwareImage {
+    pub base_address: u32,
+    pub data: Vec<u8>,
+}
+
+impl FirmwareImage {
+    /// 按扩展名加载，`.hex` / `.ihex` 为 Intel HEX，其余按二进制加载到默认地址
+    pub fn load(path: &Path) -> Result<Self, MotorError> {
+        let is_hex = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("hex") || ext.eq_ignore_ascii_case("ihex"));
+        if is_hex {
+            Self::from_ihex(&std::fs::read_to_string(path)?)
+        } else {
+            Self::from_binary(std::fs::read(path)?, DEFAULT_BASE_ADDRESS)
+        }
+    }
+
+    pub fn from_binary(data: Vec<u8>, base_address: u32) -> Result<Self, MotorError> {
+        if data.is_empty() {
+            return Err(MotorError::InvalidArgument("firmware image is empty".into()));
+        }
+        Ok(Self { base_address, data })
+    }
+
+    /// 解析 Intel HEX，并将所有数据段合并为一段连续镜像
+    pub fn from_ihex(text: &str) -> Result<Self, MotorError> {
+        let mut upper = 0u32;
+        let mut segments: Vec<(u32, Vec<u8>)> = Vec::new();
+        for record in Reader::new(text) {
+            match record.map_err(|e| MotorError::ParseError(e.to_string()))? {
+                Record::Data { offset, value } => segments.push((upper + offset as u32, value)),
+                Record::ExtendedLinearAddress(addr) => upper = (addr as u32) << 16,
+                Record::ExtendedSegmentAddress(addr) => upper = (addr as u32) << 4,
+                Record::EndOfFile => break,
+                _ => {}
+            }
+        }
+
+        let start = segments.iter().map(|(addr, _)| *addr).min()
+            .ok_or_else(|| MotorError::InvalidArgument("firmware image is empty".into()))?;
+        let end = segments.iter().map(|(addr, data)| *addr as usize + data.len()).max().unwrap_or(0);
+        let size = end - start as usize;
+        if size > MAX_IMAGE_SIZE {
+            return Err(MotorError::InvalidArgument(format!("firmware image spans {size} bytes")));
+        }
+
+        let mut data = vec![FILL_BYTE; size];
+        for (addr, value) in segments {
+            let offset = (addr - start) as usize;
+            data[offset..offset + value.len()].copy_from_slice(&value);
+        }
+        Ok(Self { base_address: start, data })
+    }
+
+    pub fn crc32(&self) -> u32 {
+        IMAGE_CRC.checksum(&self.data)
+    }
+}
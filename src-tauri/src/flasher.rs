@@ -0,0 +1,131 @@
+use crate::command::MotorFeedbackCommand;
+use crate::error::MotorError;
+use crate::events::{EventSink, MotorEvent};
+use crate::firmware_image::FirmwareImage;
+use crate::frame::{Protocol, PROTOCOL_TEXT_ACK};
+use crate::serial::SerialDevice;
+use crc::{Crc, CRC_16_IBM_3740};
+use log::warn;
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+use tokio::sync::broadcast;
+use tokio::time::{timeout, Duration};
+
+/// 每条 write 指令携带的字节数
+pub const CHUNK_SIZE: usize = 128;
+
+/// 单个数据块失败后的重试次数
+const MAX_RETRIES: usize = 3;
+
+pub const CHUNK_CRC: Crc<u16> = Crc::<u16>::new(&CRC_16_IBM_3740);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FlashStage {
+    EnterBootloader,
+    Erase,
+    Write,
+    Verify,
+    Reboot,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FlashProgress {
+    pub stage: FlashStage,
+    /// 已写入字节数
+    pub written: usize,
+    pub total: usize,
+}
+
+/// 烧录固件，bootloader 协议为文本行：
+/// ```text
+/// bootloader                          -> bootloader ready
+/// erase <addr> <size>                 -> ok
+/// write <addr> <hex data> <crc16>     -> ok | err <reason>
+/// verify <addr> <size> <crc32>        -> ok | err <reason>
+/// boot                                -> ok
+/// ```
+/// 地址与校验值均为十六进制
+pub async fn flash_firmware(serial: &SerialDevice, image: &FirmwareImage, events: &dyn EventSink) -> Result<(), MotorError> {
+    let mut rx = serial.recv_event_tx.subscribe();
+    let total = image.data.len();
+    let progress = |stage, written| events.emit(MotorEvent::FlashProgress(FlashProgress { stage, written, total }));
+
+    progress(FlashStage::EnterBootloader, 0);
+    // bootloader 只支持文本协议，先切回文本
+    if serial.binary.load(Ordering::Relaxed) {
+        let cmd = MotorFeedbackCommand::SetProtocol(Protocol::Text).to_string();
+        request(serial, &mut rx, &cmd, PROTOCOL_TEXT_ACK, Duration::from_secs(1)).await?;
+    }
+    request(serial, &mut rx, "bootloader\r\n", "bootloader ready", Duration::from_secs(3)).await?;
+
+    progress(FlashStage::Erase, 0);
+    let erase = format!("erase {:08x} {}\r\n", image.base_address, total);
+    request(serial, &mut rx, &erase, "ok", Duration::from_secs(15)).await?;
+
+    let mut written = 0;
+    for chunk in image.data.chunks(CHUNK_SIZE) {
+        let address = image.base_address + written as u32;
+        let hex: String = chunk.iter().map(|b| format!("{b:02x}")).collect();
+        let cmd = format!("write {:08x} {} {:04x}\r\n", address, hex, CHUNK_CRC.checksum(chunk));
+        let mut attempt = 0;
+        loop {
+            match request(serial, &mut rx, &cmd, "ok", Duration::from_secs(1)).await {
+                Ok(()) => break,
+                Err(MotorError::Disconnected) => return Err(MotorError::Disconnected),
+                Err(e) if attempt < MAX_RETRIES => {
+                    attempt += 1;
+                    warn!("write {address:08x} failed ({e}), retry {attempt}");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        written += chunk.len();
+        progress(FlashStage::Write, written);
+    }
+
+    progress(FlashStage::Verify, written);
+    let verify = format!("verify {:08x} {} {:08x}\r\n", image.base_address, total, image.crc32());
+    request(serial, &mut rx, &verify, "ok", Duration::from_secs(5)).await?;
+
+    progress(FlashStage::Reboot, written);
+    // 重启时 USB 可能直接断开，收不到应答也视为成功
+    match request(serial, &mut rx, "boot\r\n", "ok", Duration::from_secs(1)).await {
+        Ok(()) | Err(MotorError::Timeout) | Err(MotorError::Disconnected) => {}
+        Err(e) => return Err(e),
+    }
+    progress(FlashStage::Done, written);
+    Ok(())
+}
+
+/// 发送一条指令并等待与 `expect` 完全一致的应答，`err <reason>` 应答视为失败，其余行忽略
+async fn request(serial: &SerialDevice, rx: &mut broadcast::Receiver<String>, cmd: &str, expect: &str, wait: Duration) -> Result<(), MotorError> {
+    // 丢弃之前残留的消息
+    *rx = rx.resubscribe();
+    serial.send(cmd).await.map_err(MotorError::SerialError)?;
+    let result = timeout(wait, async {
+        loop {
+            let line = match rx.recv().await {
+                Ok(line) => line,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    serial.stats.lagged.fetch_add(n, Ordering::Relaxed);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return Err(MotorError::Disconnected),
+            };
+            if line == "__DISCONNECTED__" {
+                return Err(MotorError::Disconnected);
+            }
+            if line == expect {
+                return Ok(());
+            }
+            if line == "err" {
+                return Err(MotorError::FlashError("unknown error".into()));
+            }
+            if let Some(reason) = line.strip_prefix("err ") {
+                return Err(MotorError::FlashError(reason.trim().to_string()));
+            }
+        }
+    }).await;
+    result.unwrap_or(Err(MotorError::Timeout))
+}
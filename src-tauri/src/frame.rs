@@ -0,0 +1,176 @@
+use crate::error::MotorError;
+use crate::feedback_parser::FeedbackValue;
+use crate::line_framer::MAX_LINE_LENGTH;
+use crc::{Crc, CRC_16_IBM_3740};
+use serde::Serialize;
+use strum_macros::Display;
+
+pub const FRAME_CRC: Crc<u16> = Crc::<u16>::new(&CRC_16_IBM_3740);
+
+/// 解码后单帧的最大长度（消息 id + 负载 + CRC），文本消息可与文本协议的一行等长，超过说明丢失了分隔符
+pub const MAX_FRAME_SIZE: usize = 1 + MAX_LINE_LENGTH + 2;
+
+/// 协议切换的应答，切换指令与应答本身都使用切换前的协议
+pub const PROTOCOL_BINARY_ACK: &str = "protocol: binary";
+pub const PROTOCOL_TEXT_ACK: &str = "protocol: text";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize)]
+#[strum(serialize_all = "lowercase")]
+pub enum Protocol {
+    Text,
+    Binary,
+}
+
+/// 消息 id
+mod id {
+    pub const TEXT: u8 = 0x01;
+    pub const SET_SPEED: u8 = 0x10;
+    pub const SET_POSITION: u8 = 0x11;
+    pub const STOP: u8 = 0x12;
+    pub const SPEED: u8 = 0x20;
+    pub const POSITION: u8 = 0x21;
+    pub const CURRENT: u8 = 0x22;
+    pub const UDC: u8 = 0x23;
+}
+
+/// 二进制协议的消息，帧格式为 `COBS(id, 负载, CRC16 LE) 0x00`，浮点数均为小端。
+/// 高频的设定值和反馈使用专用消息，其余指令与应答以文本消息承载
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// 一行文本，不含换行
+    Text(String),
+    SetSpeed(f32),
+    SetPosition(f32),
+    Stop,
+    Feedback(FeedbackValue),
+}
+
+impl Message {
+    /// 将文本指令转换为消息，运行指令使用专用消息
+    pub fn from_command(line: &str) -> Self {
+        let line = line.trim();
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next().map(str::parse::<f32>), parts.next()) {
+            (Some("set_speed"), Some(Ok(speed)), None) => Message::SetSpeed(speed),
+            (Some("set_position"), Some(Ok(position)), None) => Message::SetPosition(position),
+            (Some("stop"), None, _) => Message::Stop,
+            _ => Message::Text(line.to_string()),
+        }
+    }
+
+    /// 编码为完整的帧，包含结尾的 0x00
+    pub fn encode(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity(16);
+        match self {
+            Message::Text(line) => {
+                raw.push(id::TEXT);
+                raw.extend_from_slice(line.as_bytes());
+            }
+            Message::SetSpeed(speed) => {
+                raw.push(id::SET_SPEED);
+                raw.extend_from_slice(&speed.to_le_bytes());
+            }
+            Message::SetPosition(position) => {
+                raw.push(id::SET_POSITION);
+                raw.extend_from_slice(&position.to_le_bytes());
+            }
+            Message::Stop => raw.push(id::STOP),
+            Message::Feedback(value) => {
+                let (msg_id, values) = match *value {
+                    FeedbackValue::Speed(v) => (id::SPEED, vec![v]),
+                    FeedbackValue::Position(v) => (id::POSITION, vec![v]),
+                    FeedbackValue::Current(a, b, c) => (id::CURRENT, vec![a, b, c]),
+                    FeedbackValue::Udc(v) => (id::UDC, vec![v]),
+                };
+                raw.push(msg_id);
+                values.iter().for_each(|v| raw.extend_from_slice(&v.to_le_bytes()));
+            }
+        }
+        raw.extend_from_slice(&FRAME_CRC.checksum(&raw).to_le_bytes());
+        let mut frame = cobs_encode(&raw);
+        frame.push(0);
+        frame
+    }
+
+    /// 解码一帧，参数不含结尾的 0x00
+    pub fn decode(frame: &[u8]) -> Result<Self, MotorError> {
+        let raw = cobs_decode(frame).ok_or_else(|| MotorError::ParseError("invalid COBS frame".into()))?;
+        if raw.len() < 3 || raw.len() > MAX_FRAME_SIZE {
+            return Err(MotorError::ParseError(format!("invalid frame length {}", raw.len())));
+        }
+        let (body, crc) = raw.split_at(raw.len() - 2);
+        let crc = u16::from_le_bytes([crc[0], crc[1]]);
+        if FRAME_CRC.checksum(body) != crc {
+            return Err(MotorError::ParseError("frame CRC mismatch".into()));
+        }
+        let (msg_id, payload) = (body[0], &body[1..]);
+        let floats = |n: usize| -> Result<Vec<f32>, MotorError> {
+            if payload.len() != n * 4 {
+                return Err(MotorError::ParseError(format!("invalid payload length {} for message {msg_id:#04x}", payload.len())));
+            }
+            Ok(payload.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+        };
+        let message = match msg_id {
+            id::TEXT => {
+                let line = std::str::from_utf8(payload).map_err(|e| MotorError::ParseError(e.to_string()))?;
+                Message::Text(line.trim().to_string())
+            }
+            id::SET_SPEED => Message::SetSpeed(floats(1)?[0]),
+            id::SET_POSITION => Message::SetPosition(floats(1)?[0]),
+            id::STOP => Message::Stop,
+            id::SPEED => Message::Feedback(FeedbackValue::Speed(floats(1)?[0])),
+            id::POSITION => Message::Feedback(FeedbackValue::Position(floats(1)?[0])),
+            id::CURRENT => {
+                let v = floats(3)?;
+                Message::Feedback(FeedbackValue::Current(v[0], v[1], v[2]))
+            }
+            id::UDC => Message::Feedback(FeedbackValue::Udc(floats(1)?[0])),
+            other => return Err(MotorError::ParseError(format!("unknown message id {other:#04x}"))),
+        };
+        Ok(message)
+    }
+}
+
+/// COBS 编码，结果中不含 0x00
+pub fn cobs_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 254 + 2);
+    let mut code_index = 0;
+    out.push(0);
+    let mut code = 1u8;
+    for &byte in data {
+        if byte != 0 {
+            out.push(byte);
+            code += 1;
+        }
+        if byte == 0 || code == 0xff {
+            out[code_index] = code;
+            code_index = out.len();
+            out.push(0);
+            code = 1;
+        }
+    }
+    out[code_index] = code;
+    out
+}
+
+/// COBS 解码，数据中出现 0x00 或长度不符时返回 None
+pub fn cobs_decode(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let code = data[i] as usize;
+        if code == 0 || i + code > data.len() {
+            return None;
+        }
+        let block = &data[i + 1..i + code];
+        if block.contains(&0) {
+            return None;
+        }
+        out.extend_from_slice(block);
+        i += code;
+        if code < 0xff && i < data.len() {
+            out.push(0);
+        }
+    }
+    Some(out)
+}
@@ -1,254 +1,511 @@
-use crate::command::{MotorConfigCommand, MotorRunCommand};
+use crate::command::{MotorConfigCommand, MotorRunCommand, MotorState};
 use crate::config_parser::{EncoderDirection, EncoderType, MotorConfig};
+use crate::error::MotorError;
+use crate::estop::{clear_emergency_stop, emergency_stop_all, is_emergency_stopped, EmergencyStopReport};
+use crate::events::{MotorEvent, TauriEventSink};
+use crate::fault_parser::FaultRecord;
+use crate::firmware_image::FirmwareImage;
+use crate::frame::Protocol;
+use crate::link_stats::LinkReport;
 use crate::motor::{Motor, MotorFeedbackState};
-use crate::serial::SerialDevice;
+use crate::probe::{probe_port, ProbeResult, DEFAULT_BAUD_RATES, PROBE_TIMEOUT};
+use crate::reconnect::{ReconnectPolicy, ReconnectState, UsbIdentity};
+use crate::safety::SafetyLimits;
+use crate::script::{run_script, ScriptControl, ScriptReport};
+use crate::serial::{SerialDevice, SerialPortDescriptor};
+use crate::sweep::{run_gain_sweep, SweepRequest, SweepResult};
+use crate::trajectory::{play_trajectory, Trajectory};
+use crate::version_parser::FirmwareInfo;
+use crate::watchdog::HostHeartbeat;
+use crate::waveform::{SetpointTarget, Waveform};
+use log::warn;
+use std::collections::VecDeque;
+use std::path::PathBuf;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+use tokio_serial::SerialPortInfo;
 
 pub struct AppState {
     pub app: AppHandle,
     pub motor: Arc<Mutex<Option<Arc<Motor>>>>,
+    pub script: Arc<ScriptControl>,
+    pub reconnect: Mutex<ReconnectState>,
+    /// 原始指令历史，断开重连后保留
+    pub raw_history: Mutex<VecDeque<String>>,
+    /// 前端心跳，不经过 motor 锁，避免长时间操作期间心跳被阻塞
+    pub heartbeat: Arc<HostHeartbeat>,
 }
 
+/// 原始指令历史的最大条数
+const RAW_HISTORY_LIMIT: usize = 200;
+
 #[tauri::command]
-pub async fn list_serial_ports() -> Result<Vec<String>, String> {
-    let ports = tokio_serial::available_ports()
-        .map_err(|e| e.to_string())?
-        .into_iter()
-        .map(|p| p.port_name)
-        .collect();
-    Ok(ports)
+pub async fn list_serial_ports() -> Result<Vec<SerialPortDescriptor>, MotorError> {
+    Ok(SerialPortDescriptor::list()?)
 }
 
 #[tauri::command]
-pub async fn connect_motor(port_name: String, baud_rate: u32, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let mut motor_guard = state.motor.lock().await;
-    if motor_guard.is_some() {
-        Err(format!("Motor is already connected in port {}", motor_guard.as_ref().unwrap().serial.port_name))
-    } else {
+pub async fn probe_serial_port(state: tauri::State<'_, AppState>, port_name: String, baud_rates: Option<Vec<u32>>) -> Result<ProbeResult, MotorError> {
+    if let Some(motor) = state.motor.lock().await.as_ref() {
+        if motor.serial.port_name == port_name {
+            return Err(MotorError::Busy(format!("{port_name} is already connected")));
+        }
+    }
+    let baud_rates = baud_rates.unwrap_or_else(|| DEFAULT_BAUD_RATES.to_vec());
+    probe_port(&port_name, &baud_rates, PROBE_TIMEOUT).await
+}
+
+impl AppState {
+    /// 取出当前连接的电机，调用方在操作期间不持有全局锁，避免长时间操作阻塞其他命令
+    pub async fn current_motor(&self) -> Result<Arc<Motor>, MotorError> {
+        self.motor.lock().await.clone().ok_or(MotorError::NotConnected)
+    }
+
+    async fn open_motor(&self, port_name: String, baud_rate: u32) -> Result<Arc<Motor>, MotorError> {
         let port = SerialDevice::new(port_name, baud_rate);
-        port.connect().await?;
-        let motor = Motor::new(port, state.app.clone());
-        motor.start_parse_feedback_loop().await;
-        *motor_guard = Some(motor);
+        port.connect().await.map_err(MotorError::SerialError)?;
+        Motor::open(port, Arc::new(TauriEventSink(self.app.clone())), Some(Arc::clone(&self.heartbeat))).await
+    }
+
+    async fn close_motor(motor: &Arc<Motor>) -> Result<(), MotorError> {
+        // 主动断开，不再自动重连
+        motor.auto_reconnect.store(false, Ordering::Relaxed);
+        motor.close().await
+    }
+
+    pub async fn connect(&self, port_name: String, baud_rate: u32) -> Result<(), MotorError> {
+        let mut motor_guard = self.motor.lock().await;
+        if motor_guard.is_some() {
+            Err(MotorError::AlreadyConnected { port: motor_guard.as_ref().unwrap().serial.port_name.clone() })
+        } else {
+            let identity = UsbIdentity::lookup(&port_name);
+            self.heartbeat.disarm();
+            let motor = self.open_motor(port_name, baud_rate).await?;
+            let mut reconnect = self.reconnect.lock().await;
+            reconnect.identity = identity;
+            reconnect.lost_at = None;
+            motor.auto_reconnect.store(reconnect.can_reconnect(), Ordering::Relaxed);
+            *motor_guard = Some(motor);
+            drop(motor_guard);
+            Ok(())
+        }
+    }
+
+    pub async fn disconnect(&self) -> Result<(), MotorError> {
+        let mut motor_guard = self.motor.lock().await;
+        if let Some(motor) = motor_guard.take() {
+            let mut reconnect = self.reconnect.lock().await;
+            reconnect.identity = None;
+            reconnect.lost_at = None;
+            drop(reconnect);
+            Self::close_motor(&motor).await?;
+            *motor_guard = None;
+            Ok(())
+        } else {
+            Err(MotorError::NotConnected)
+        }
+    }
+
+    /// 烧录固件，完成后断开连接，设备重启后需要重新连接
+    pub async fn flash_firmware(&self, image: FirmwareImage) -> Result<(), MotorError> {
+        let mut motor_guard = self.motor.lock().await;
+        let Some(motor) = motor_guard.as_ref() else {
+            return Err(MotorError::NotConnected);
+        };
+        let motor_state = motor.state();
+        if motor_state != MotorState::Stop {
+            return Err(MotorError::invalid_state(motor_state, "flash firmware"));
+        }
+        // 烧录期间不接受其他指令
+        let motor = motor_guard.take().unwrap();
+        let mut reconnect = self.reconnect.lock().await;
+        reconnect.identity = None;
+        reconnect.lost_at = None;
+        drop(reconnect);
         drop(motor_guard);
+
+        let result = motor.flash_firmware(&image).await;
+        let _ = Self::close_motor(&motor).await;
+        result
+    }
+
+    /// 急停所有串口，不等待任何锁，由命令和全局快捷键触发
+    pub async fn emergency_stop(&self) -> EmergencyStopReport {
+        self.script.abort();
+        let report = emergency_stop_all().await;
+        // 连接或断开过程中锁被占用时跳过，设定值流等会因急停锁存自行退出
+        let motor = self.motor.try_lock().ok().and_then(|guard| guard.clone());
+        if let Some(motor) = motor {
+            motor.mark_emergency_stopped();
+        }
+        if !report.is_confirmed() {
+            warn!("Emergency stop not confirmed: {report:?}");
+        }
+        if let Err(e) = self.app.emit("emergency-stop", &report) {
+            warn!("Tauri emit error {e}");
+        }
+        report
+    }
+
+    /// 由串口监视任务定期调用，连接中断后在端口列表中查找同一设备并重新连接
+    pub async fn poll_reconnect(&self, ports: &[SerialPortInfo]) {
+        let Some(motor) = self.motor.lock().await.clone() else {
+            return;
+        };
+        if motor.serial.connected.load(Ordering::Relaxed) || !motor.auto_reconnect.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut reconnect = self.reconnect.lock().await;
+        reconnect.lost_at.get_or_insert_with(Instant::now);
+        if reconnect.is_expired() {
+            // 超时放弃，按普通断开处理
+            reconnect.lost_at = None;
+            motor.auto_reconnect.store(false, Ordering::Relaxed);
+            motor.events.emit(MotorEvent::Disconnected);
+            return;
+        }
+        let Some(port_name) = reconnect.identity.as_ref().and_then(|id| id.find_in(ports)) else {
+            return;
+        };
+        let port_name = port_name.to_string();
+        drop(reconnect);
+
+        match self.reconnect_to(&motor, port_name).await {
+            Ok(()) => self.reconnect.lock().await.lost_at = None,
+            Err(e) => warn!("Reconnect failed: {e}"),
+        }
+    }
+
+    /// 用新的串口替换已断开的电机，并恢复反馈订阅和配置
+    async fn reconnect_to(&self, old: &Arc<Motor>, port_name: String) -> Result<(), MotorError> {
+        let mut motor_guard = self.motor.lock().await;
+        // 等待期间用户可能已主动断开
+        if !motor_guard.as_ref().is_some_and(|m| Arc::ptr_eq(m, old)) {
+            return Ok(());
+        }
+        let feedback = *old.feedback.lock().await;
+        let motor = self.open_motor(port_name.clone(), old.serial.baud_rate).await?;
+        *motor.safety.lock().await = *old.safety.lock().await;
+        // 急停锁存需要手动解除，重连不能绕过
+        motor.serial.emergency_stopped.store(old.serial.emergency_stopped.load(Ordering::Relaxed), Ordering::Relaxed);
+        motor.auto_reconnect.store(true, Ordering::Relaxed);
+        let restored = async {
+            motor.load_config().await?;
+            motor.set_feedback(feedback).await
+        }.await;
+        if let Err(e) = restored {
+            let _ = Self::close_motor(&motor).await;
+            return Err(e);
+        }
+        Self::close_motor(old).await?;
+        *motor_guard = Some(Arc::clone(&motor));
+        motor.events.emit(MotorEvent::Reconnected(port_name));
         Ok(())
     }
 }
 
 #[tauri::command]
-pub async fn disconnect_motor(state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let mut motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.take() {
-        motor.serial.disconnect().await?;
-        // 等待 parse loop 停止
-        motor.stop_parse_feedback_loop().await;
-        *motor_guard = None;
-        Ok(())
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn connect_motor(port_name: String, baud_rate: u32, state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    state.connect(port_name, baud_rate).await
 }
 
 #[tauri::command]
-pub async fn get_motor_port(state: tauri::State<'_, AppState>) -> Result<String, ()> {
+pub async fn disconnect_motor(state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    state.disconnect().await
+}
+
+#[tauri::command]
+pub async fn get_motor_port(state: tauri::State<'_, AppState>) -> Result<String, MotorError> {
     match state.motor.lock().await.as_ref() {
         Some(motor) => Ok(motor.serial.port_name.clone()),
-        None => Err(()),
+        None => Err(MotorError::NotConnected),
     }
 }
 
+/// 前端定期调用，DebugRun 时停止调用会触发停机。
+/// 窗口隐藏时前端传入 `visible = false` 暂停监视，部分平台无法关闭后台定时器降频
 #[tauri::command]
-pub async fn get_motor_state(state: tauri::State<'_, AppState>) -> Result<String, String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        let motor_state = motor.state.lock().await;
-        Ok((*motor_state).to_string().clone())
+pub async fn motor_heartbeat(visible: bool, state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    if visible {
+        state.heartbeat.ping();
     } else {
-        Err("Motor is not connected".to_string())
+        state.heartbeat.disarm();
     }
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn set_motor_feedback(state: tauri::State<'_, AppState>, feedback: MotorFeedbackState) -> Result<(), String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.set_feedback(feedback).await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn get_motor_state(state: tauri::State<'_, AppState>) -> Result<String, MotorError> {
+    // 直接读取本地状态，不等待正在进行的操作，与下位机的对齐由周期状态同步完成
+    let motor = state.current_motor().await?;
+    Ok(motor.state().to_string())
 }
 
 #[tauri::command]
-pub async fn get_motor_config(state: tauri::State<'_, AppState>) -> Result<MotorConfig, String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        let config = motor.motor_config.lock().await;
-        if let Some(config) = (*config).as_ref() {
-            // 如果配置已存在就直接返回
-            Ok(config.clone())
-        } else {
-            drop(config);
-            // 否则先加载再返回
-            motor.load_config().await.map_err(|e| e.to_string())
-        }
-    } else {
-        Err("Motor is not connected".to_string())
+pub async fn get_firmware_info(state: tauri::State<'_, AppState>) -> Result<FirmwareInfo, MotorError> {
+    let motor = state.current_motor().await?;
+    let cached = motor.firmware.lock().await.clone();
+    match cached {
+        Some(info) => Ok(info),
+        None => motor.query_firmware().await,
     }
 }
 
 #[tauri::command]
-pub async fn refresh_motor_config(state: tauri::State<'_, AppState>) -> Result<MotorConfig, String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.load_config().await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn get_motor_protocol(state: tauri::State<'_, AppState>) -> Result<Protocol, MotorError> {
+    let motor = state.current_motor().await?;
+    Ok(motor.protocol())
 }
 
 #[tauri::command]
-pub async fn config_motor_position_pid(kp: f32, ki: f32, kd: f32, output_max: f32, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.send_config_command(
-            &MotorConfigCommand::ConfigPositionPid { kp, ki, kd, output_max },
-        ).await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn get_link_stats(state: tauri::State<'_, AppState>) -> Result<LinkReport, MotorError> {
+    let motor = state.current_motor().await?;
+    Ok(motor.link_report().await)
 }
 
 #[tauri::command]
-pub async fn config_motor_speed_pi(kp: f32, ki: f32, output_max: f32, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.send_config_command(
-            &MotorConfigCommand::ConfigSpeedPi { kp, ki, output_max },
-        ).await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn set_motor_feedback(state: tauri::State<'_, AppState>, feedback: MotorFeedbackState) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.set_feedback(feedback).await
 }
 
 #[tauri::command]
-pub async fn config_motor_current_pi(id_kp: f32, id_ki: f32, iq_kp: f32, iq_ki: f32, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.send_config_command(
-            &MotorConfigCommand::ConfigCurrentPi { id_kp, id_ki, iq_kp, iq_ki },
-        ).await.map_err(|e| e.to_string())
+pub async fn get_motor_config(state: tauri::State<'_, AppState>) -> Result<MotorConfig, MotorError> {
+    let motor = state.current_motor().await?;
+    let config = motor.motor_config.lock().await;
+    if let Some(config) = (*config).as_ref() {
+        // 如果配置已存在就直接返回
+        Ok(config.clone())
     } else {
-        Err("Motor is not connected".to_string())
+        drop(config);
+        // 否则先加载再返回
+        motor.load_config().await
     }
 }
 
 #[tauri::command]
-pub async fn config_motor_encoder(pole_pairs: u32, encoder_direction: EncoderDirection, encoder_offset: f32, encoder_type: EncoderType, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.send_config_command(
-            &MotorConfigCommand::ConfigEncoder { pole_pairs, encoder_direct: encoder_direction as i8, encoder_offset, encoder_type: encoder_type.to_string() },
-        ).await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn refresh_motor_config(state: tauri::State<'_, AppState>) -> Result<MotorConfig, MotorError> {
+    let motor = state.current_motor().await?;
+    motor.load_config().await
 }
 
 #[tauri::command]
-pub async fn motor_calibration(state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.calibration().await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn config_motor_position_pid(kp: f32, ki: f32, kd: f32, output_max: f32, state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.send_config_command(
+        &MotorConfigCommand::ConfigPositionPid { kp, ki, kd, output_max },
+    ).await
 }
 
 #[tauri::command]
-pub async fn is_motor_config_unsaved(state: tauri::State<'_, AppState>) -> Result<bool, String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        Ok(motor.unsaved.load(Ordering::Relaxed))
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn config_motor_speed_pi(kp: f32, ki: f32, output_max: f32, state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.send_config_command(
+        &MotorConfigCommand::ConfigSpeedPi { kp, ki, output_max },
+    ).await
 }
 
 #[tauri::command]
-pub async fn save_motor_config(state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.save_config().await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn config_motor_current_pi(id_kp: f32, id_ki: f32, iq_kp: f32, iq_ki: f32, state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.send_config_command(
+        &MotorConfigCommand::ConfigCurrentPi { id_kp, id_ki, iq_kp, iq_ki },
+    ).await
 }
 
 #[tauri::command]
-pub async fn config_motor_id(state: tauri::State<'_, AppState>, id: u8) -> Result<(), String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.send_config_command(
-            &MotorConfigCommand::ConfigId(id),
-        ).await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn config_motor_encoder(pole_pairs: u32, encoder_direction: EncoderDirection, encoder_offset: f32, encoder_type: EncoderType, state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.send_config_command(
+        &MotorConfigCommand::ConfigEncoder { pole_pairs, encoder_direct: encoder_direction as i8, encoder_offset, encoder_type: encoder_type.to_string() },
+    ).await
 }
 
 #[tauri::command]
-pub async fn config_motor_udc(state: tauri::State<'_, AppState>, udc: f32) -> Result<(), String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.send_config_command(
-            &MotorConfigCommand::ConfigUdc(udc),
-        ).await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn motor_calibration(state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.calibration().await
 }
 
 #[tauri::command]
-pub async fn config_motor_idq_filter(state: tauri::State<'_, AppState>, fc: f32) -> Result<(), String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.send_config_command(
-            &MotorConfigCommand::ConfigIdqFilter(fc),
-        ).await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn is_motor_config_unsaved(state: tauri::State<'_, AppState>) -> Result<bool, MotorError> {
+    let motor = state.current_motor().await?;
+    Ok(motor.unsaved.load(Ordering::Relaxed))
 }
 
 #[tauri::command]
-pub async fn motor_stop(state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.send_running_command(&MotorRunCommand::Stop).await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
-    }
+pub async fn save_motor_config(state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.save_config().await
 }
 
 #[tauri::command]
-pub async fn motor_set_speed(speed: f32, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let motor_guard = state.motor.lock().await;
-    if let Some(motor) = motor_guard.as_ref() {
-        motor.send_running_command(&MotorRunCommand::SetSpeed(speed)).await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
+pub async fn config_motor_id(state: tauri::State<'_, AppState>, id: u8) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.send_config_command(
+        &MotorConfigCommand::ConfigId(id),
+    ).await
+}
+
+#[tauri::command]
+pub async fn config_motor_udc(state: tauri::State<'_, AppState>, udc: f32) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.send_config_command(
+        &MotorConfigCommand::ConfigUdc(udc),
+    ).await
+}
+
+#[tauri::command]
+pub async fn config_motor_idq_filter(state: tauri::State<'_, AppState>, fc: f32) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.send_config_command(
+        &MotorConfigCommand::ConfigIdqFilter(fc),
+    ).await
+}
+
+#[tauri::command]
+pub async fn motor_stop(state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.send_running_command(&MotorRunCommand::Stop).await
+}
+
+#[tauri::command]
+pub async fn motor_emergency_stop(state: tauri::State<'_, AppState>) -> Result<EmergencyStopReport, MotorError> {
+    Ok(state.emergency_stop().await)
+}
+
+#[tauri::command]
+pub async fn clear_motor_emergency_stop() -> Result<(), MotorError> {
+    clear_emergency_stop();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn is_motor_emergency_stopped() -> Result<bool, MotorError> {
+    Ok(is_emergency_stopped())
+}
+
+#[tauri::command]
+pub async fn motor_clear_fault(state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.clear_fault().await
+}
+
+#[tauri::command]
+pub async fn get_fault_log(state: tauri::State<'_, AppState>) -> Result<Vec<FaultRecord>, MotorError> {
+    let motor = state.current_motor().await?;
+    let log = motor.fault_log.lock().await.iter().cloned().collect();
+    Ok(log)
+}
+
+#[tauri::command]
+pub async fn get_safety_limits(state: tauri::State<'_, AppState>) -> Result<SafetyLimits, MotorError> {
+    let motor = state.current_motor().await?;
+    let limits = *motor.safety.lock().await;
+    Ok(limits)
+}
+
+#[tauri::command]
+pub async fn set_safety_limits(state: tauri::State<'_, AppState>, limits: SafetyLimits) -> Result<(), MotorError> {
+    limits.validate().map_err(MotorError::InvalidArgument)?;
+    let motor = state.current_motor().await?;
+    *motor.safety.lock().await = limits;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn motor_set_speed(speed: f32, state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.send_running_command(&MotorRunCommand::SetSpeed(speed)).await
+}
+
+#[tauri::command]
+pub async fn motor_set_position(position: f32, state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    let motor = state.current_motor().await?;
+    motor.send_running_command(&MotorRunCommand::SetPosition(position)).await
+}
+
+#[tauri::command]
+pub async fn motor_gain_sweep(request: SweepRequest, state: tauri::State<'_, AppState>) -> Result<Vec<SweepResult>, MotorError> {
+    let motor = state.current_motor().await?;
+    run_gain_sweep(&motor, &request).await
+}
+
+#[tauri::command]
+pub async fn motor_start_waveform(target: SetpointTarget, waveform: Waveform, rate_hz: f32, state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    waveform.validate()?;
+    let motor = state.current_motor().await?;
+    motor.start_setpoint_stream(target, rate_hz, move |t| Some(waveform.value_at(t)))
+        .await
+        .map(|_| ())
+}
+
+#[tauri::command]
+pub async fn motor_play_trajectory(path: PathBuf, target: SetpointTarget, rate_hz: f32, state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    let trajectory = Trajectory::load(&path)?;
+    let motor = state.current_motor().await?;
+    play_trajectory(&motor, trajectory, target, rate_hz).await
+}
+
+#[tauri::command]
+pub async fn run_bench_script(source: String, state: tauri::State<'_, AppState>) -> Result<ScriptReport, MotorError> {
+    run_script(state.app.clone(), Arc::clone(&state.script), source).await
+}
+
+#[tauri::command]
+pub async fn abort_bench_script(state: tauri::State<'_, AppState>) -> Result<(), MotorError> {
+    state.script.abort();
+    Ok(())
+}
+#[tauri::command]
+pub async fn send_raw_line(state: tauri::State<'_, AppState>, line: String, wait_for: Option<String>, timeout_ms: Option<u64>) -> Result<Option<String>, MotorError> {
+    let motor = state.current_motor().await?;
+    let rx = motor.send_raw_line(&line).await?;
+    {
+        // 只记录实际发送的指令，连续重复的只记录一次
+        let mut history = state.raw_history.lock().await;
+        if history.back() != Some(&line) {
+            history.push_back(line.clone());
+            if history.len() > RAW_HISTORY_LIMIT {
+                history.pop_front();
+            }
+        }
     }
+    let Some(pattern) = wait_for else {
+        return Ok(None);
+    };
+    let wait = Duration::from_millis(timeout_ms.unwrap_or(1000));
+    motor.wait_raw_reply(rx, &pattern, wait).await.map(Some)
 }
 
 #[tauri::command]
-pub async fn motor_set_position(position: f32, state: tauri::State<'_, AppState>) -> Result<(), String> {
+pub async fn get_raw_history(state: tauri::State<'_, AppState>) -> Result<Vec<String>, MotorError> {
+    Ok(state.raw_history.lock().await.iter().cloned().collect())
+}
+
+#[tauri::command]
+pub async fn flash_motor_firmware(state: tauri::State<'_, AppState>, path: PathBuf) -> Result<(), MotorError> {
+    let image = FirmwareImage::load(&path)?;
+    state.flash_firmware(image).await
+}
+
+#[tauri::command]
+pub async fn get_reconnect_policy(state: tauri::State<'_, AppState>) -> Result<ReconnectPolicy, MotorError> {
+    Ok(state.reconnect.lock().await.policy)
+}
+
+#[tauri::command]
+pub async fn set_reconnect_policy(state: tauri::State<'_, AppState>, policy: ReconnectPolicy) -> Result<(), MotorError> {
+    // 与 connect 保持相同的加锁顺序
     let motor_guard = state.motor.lock().await;
+    let mut reconnect = state.reconnect.lock().await;
+    reconnect.policy = policy;
     if let Some(motor) = motor_guard.as_ref() {
-        motor.send_running_command(&MotorRunCommand::SetPosition(position)).await.map_err(|e| e.to_string())
-    } else {
-        Err("Motor is not connected".to_string())
+        motor.auto_reconnect.store(reconnect.can_reconnect(), Ordering::Relaxed);
     }
-}
\ No newline at end of file
+    Ok(())
+}
@@ -1,30 +1,51 @@
-use crate::invokes::{config_motor_current_pi, config_motor_encoder, config_motor_id, config_motor_idq_filter, config_motor_position_pid, config_motor_speed_pi, config_motor_udc, connect_motor, disconnect_motor, get_motor_config, get_motor_port, get_motor_state, is_motor_config_unsaved, list_serial_ports, motor_calibration, motor_set_position, motor_set_speed, motor_stop, refresh_motor_config, save_motor_config, set_motor_feedback, AppState};
+use crate::invokes::{abort_bench_script, clear_motor_emergency_stop, config_motor_current_pi, config_motor_encoder, config_motor_id, config_motor_idq_filter, config_motor_position_pid, config_motor_speed_pi, config_motor_udc, connect_motor, disconnect_motor, flash_motor_firmware, get_fault_log, get_firmware_info, get_link_stats, get_motor_config, get_motor_port, get_motor_protocol, get_motor_state, get_raw_history, get_reconnect_policy, get_safety_limits, is_motor_config_unsaved, is_motor_emergency_stopped, list_serial_ports, motor_calibration, motor_clear_fault, motor_emergency_stop, motor_gain_sweep, motor_heartbeat, motor_play_trajectory, motor_set_position, motor_set_speed, motor_start_waveform, motor_stop, probe_serial_port, refresh_motor_config, run_bench_script, save_motor_config, send_raw_line, set_motor_feedback, set_reconnect_policy, set_safety_limits, AppState};
+use crate::serial::SerialPortDescriptor;
 use log::debug;
 use std::sync::Arc;
 use tauri::{Emitter, Manager};
 use tokio::sync::Mutex;
 
-mod serial;
-mod motor;
-mod error;
-mod command;
-mod config_parser;
+pub mod serial;
+pub mod motor;
+pub mod error;
+pub mod estop;
+pub mod fault_parser;
+pub mod events;
+pub mod command;
+pub mod config_parser;
+pub mod device_log;
 mod invokes;
-mod calibration_parser;
+pub mod probe;
+pub mod calibration_parser;
 mod exit_signal;
+pub mod feedback_parser;
+pub mod firmware_image;
+pub mod flasher;
+pub mod frame;
+pub mod line_framer;
+pub mod link_stats;
+pub mod reconnect;
+pub mod safety;
+mod script;
+pub mod sweep;
+pub mod trajectory;
+pub mod version_parser;
+pub mod waveform;
+pub mod watchdog;
 
 pub fn start_serial_monitor(app: tauri::AppHandle) {
     tauri::async_runtime::spawn(async move {
-        let mut last_ports: Vec<String> = vec![];
+        let mut last_ports: Vec<SerialPortDescriptor> = vec![];
 
         loop {
-            let current = match list_serial_ports().await {
+            let ports = match tokio_serial::available_ports() {
                 Ok(ports) => ports,
                 Err(e) => {
                     debug!("Failed to list serial ports: {}", e);
                     continue;
                 }
             };
+            let current: Vec<SerialPortDescriptor> = ports.iter().map(SerialPortDescriptor::from).collect();
 
             if current != last_ports {
                 last_ports = current.clone();
@@ -33,11 +54,42 @@ pub fn start_serial_monitor(app: tauri::AppHandle) {
                     .unwrap();
             }
 
+            // 连接中断时查找同一设备并自动重连
+            app.state::<AppState>().poll_reconnect(&ports).await;
+
             tokio::time::sleep(std::time::Duration::from_secs(1)).await;
         }
     });
 }
 
+/// 急停全局快捷键，窗口不在前台时也有效
+#[cfg(desktop)]
+const EMERGENCY_STOP_SHORTCUT: &str = "CommandOrControl+Shift+Space";
+
+#[cfg(desktop)]
+fn register_emergency_stop_shortcut(app: &tauri::AppHandle) -> tauri::Result<()> {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+    let shortcut: Shortcut = EMERGENCY_STOP_SHORTCUT.parse().expect("invalid emergency stop shortcut");
+    app.plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(move |app, pressed, event| {
+                if pressed == &shortcut && event.state == ShortcutState::Pressed {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        app.state::<AppState>().emergency_stop().await;
+                    });
+                }
+            })
+            .build(),
+    )?;
+    // 快捷键被其他程序占用时不影响启动，界面上的急停按钮仍然可用
+    if let Err(e) = app.global_shortcut().register(shortcut) {
+        log::warn!("Failed to register emergency stop shortcut: {e}");
+    }
+    Ok(())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -47,16 +99,26 @@ pub fn run() {
             let state = AppState {
                 motor: Arc::new(Mutex::new(None)),
                 app: handle.clone(),
+                script: Default::default(),
+                reconnect: Default::default(),
+                raw_history: Default::default(),
+                heartbeat: Default::default(),
             };
-            start_serial_monitor(handle.clone());
             app.manage(state);
+            start_serial_monitor(handle.clone());
+            #[cfg(desktop)]
+            register_emergency_stop_shortcut(handle)?;
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
              list_serial_ports,
+            probe_serial_port,
             connect_motor,
             disconnect_motor,
             get_motor_state,
+            get_firmware_info,
+            get_motor_protocol,
+            get_link_stats,
             set_motor_feedback,
             get_motor_config,
             refresh_motor_config,
@@ -72,8 +134,26 @@ pub fn run() {
             config_motor_udc,
             config_motor_idq_filter,
             motor_stop,
+            motor_emergency_stop,
+            clear_motor_emergency_stop,
+            is_motor_emergency_stopped,
             motor_set_speed,
-            motor_set_position
+            motor_set_position,
+            motor_gain_sweep,
+            motor_start_waveform,
+            motor_play_trajectory,
+            run_bench_script,
+            abort_bench_script,
+            get_reconnect_policy,
+            set_reconnect_policy,
+            flash_motor_firmware,
+            send_raw_line,
+            get_raw_history,
+            motor_clear_fault,
+            get_fault_log,
+            motor_heartbeat,
+            get_safety_limits,
+            set_safety_limits
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
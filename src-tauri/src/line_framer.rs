@@ -0,0 +1,109 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 单行的最大字节数，超过说明下位机没有发送换行或数据已错乱
+pub const MAX_LINE_LENGTH: usize = 1024;
+
+/// 分帧统计
+#[derive(Debug, Default)]
+pub struct FramingStats {
+    pub lines: AtomicU64,
+    /// 超长被丢弃的行
+    pub overflows: AtomicU64,
+    pub overflow_bytes: AtomicU64,
+    /// 含有非 UTF-8 字节的行
+    pub invalid_utf8: AtomicU64,
+    /// 二进制协议下 COBS 或 CRC 校验失败的帧
+    pub corrupted_frames: AtomicU64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Framed {
+    Line(String),
+    /// 超长行被丢弃，参数为丢弃的字节数
+    Overflow(usize),
+}
+
+/// 按行分割串口数据，`\r`、`\n`、`\r\n` 均视为行结束，跨读取的多字节字符和 `\r\n` 都能正确处理
+#[derive(Debug)]
+pub struct LineFramer {
+    buf: Vec<u8>,
+    max_len: usize,
+    /// 上一行以 `\r` 结束，紧随的 `\n` 属于同一个换行
+    skip_lf: bool,
+    /// 当前行已超长，丢弃到下一个换行为止
+    dropped: usize,
+}
+
+impl LineFramer {
+    pub fn new(max_len: usize) -> Self {
+        Self { buf: Vec::new(), max_len, skip_lf: false, dropped: 0 }
+    }
+
+    /// 读取到下一行结束为止，返回消耗的字节数与结果；没有完整的行时消耗全部数据并返回 None。
+    /// 每次只返回一行，调用方可以在两行之间切换协议
+    pub fn feed(&mut self, data: &[u8], stats: &FramingStats) -> (usize, Option<Framed>) {
+        for (i, &byte) in data.iter().enumerate() {
+            let skip_lf = std::mem::take(&mut self.skip_lf);
+            match byte {
+                b'\n' if skip_lf => continue,
+                b'\r' | b'\n' => {
+                    self.skip_lf = byte == b'\r';
+                    return (i + 1, Some(self.finish_line(stats)));
+                }
+                // 文本中不会出现 0x00，是协议切换时残留的二进制帧，连同之前的内容一起丢弃
+                0 => {
+                    self.buf.clear();
+                    self.dropped = 0;
+                }
+                _ if self.dropped > 0 || self.buf.len() >= self.max_len => {
+                    // 超长部分直接丢弃，缓存不再增长
+                    self.dropped += self.buf.len() + 1;
+                    self.buf.clear();
+                }
+                _ => self.buf.push(byte),
+            }
+        }
+        (data.len(), None)
+    }
+
+    /// 切换到二进制协议后调用，返回上一行 `\r\n` 中尚未读到的 `\n` 的字节数，需从数据中跳过
+    pub fn pending_lf(&mut self, data: &[u8]) -> usize {
+        match data.first() {
+            Some(&byte) => usize::from(std::mem::take(&mut self.skip_lf) && byte == b'\n'),
+            None => 0,
+        }
+    }
+
+    fn finish_line(&mut self, stats: &FramingStats) -> Framed {
+        if self.dropped > 0 {
+            let dropped = std::mem::take(&mut self.dropped);
+            stats.overflows.fetch_add(1, Ordering::Relaxed);
+            stats.overflow_bytes.fetch_add(dropped as u64, Ordering::Relaxed);
+            return Framed::Overflow(dropped);
+        }
+        let line = decode_line(&self.buf, stats);
+        if !self.buf.is_empty() {
+            stats.lines.fetch_add(1, Ordering::Relaxed);
+        }
+        self.buf.clear();
+        Framed::Line(line)
+    }
+}
+
+/// 转换为文本，非 UTF-8 字节保留为 `\xNN`，便于在控制台中查看原始数据
+fn decode_line(bytes: &[u8], stats: &FramingStats) -> String {
+    let mut line = String::with_capacity(bytes.len());
+    let mut invalid = false;
+    for chunk in bytes.utf8_chunks() {
+        line.push_str(chunk.valid());
+        for byte in chunk.invalid() {
+            invalid = true;
+            let _ = write!(line, "\\x{byte:02x}");
+        }
+    }
+    if invalid {
+        stats.invalid_utf8.fetch_add(1, Ordering::Relaxed);
+    }
+    line
+}
@@ -0,0 +1,138 @@
+use crate::line_framer::FramingStats;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+use std::sync::Mutex;
+use tokio::time::Duration;
+
+/// 应答延迟超过该值认为链路状况不佳
+const LATENCY_DEGRADED: Duration = Duration::from_millis(200);
+
+/// 串口链路统计，计数只增不减，速率在周期上报时由两次快照计算
+#[derive(Debug, Default)]
+pub struct LinkStats {
+    pub framing: FramingStats,
+    pub bytes_in: AtomicU64,
+    pub bytes_out: AtomicU64,
+    pub commands_sent: AtomicU64,
+    /// 收到的文本行（含反馈）
+    pub lines_received: AtomicU64,
+    /// 解析出的反馈数据
+    pub feedback_received: AtomicU64,
+    /// 看起来是反馈但解析失败的行
+    pub parse_failures: AtomicU64,
+    /// 订阅者处理不及时被丢弃的广播消息
+    pub lagged: AtomicU64,
+    latency: Mutex<LatencyStats>,
+}
+
+/// 请求-应答指令的延迟
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct LatencyStats {
+    pub count: u64,
+    pub last_ms: f32,
+    pub avg_ms: f32,
+    pub max_ms: f32,
+}
+
+/// 计数快照
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct LinkCounters {
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub commands_sent: u64,
+    pub lines_received: u64,
+    pub feedback_received: u64,
+    pub parse_failures: u64,
+    pub corrupted_frames: u64,
+    pub overflows: u64,
+    pub invalid_utf8: u64,
+    pub lagged: u64,
+}
+
+impl LinkCounters {
+    fn errors(&self) -> u64 {
+        self.parse_failures + self.corrupted_frames + self.overflows + self.invalid_utf8 + self.lagged
+    }
+}
+
+impl LinkStats {
+    pub fn record_latency(&self, elapsed: Duration) {
+        let ms = elapsed.as_secs_f32() * 1000f32;
+        let mut latency = self.latency.lock().unwrap();
+        latency.count += 1;
+        latency.last_ms = ms;
+        latency.avg_ms += (ms - latency.avg_ms) / latency.count as f32;
+        latency.max_ms = latency.max_ms.max(ms);
+    }
+
+    pub fn latency(&self) -> LatencyStats {
+        *self.latency.lock().unwrap()
+    }
+
+    pub fn snapshot(&self) -> LinkCounters {
+        LinkCounters {
+            bytes_in: self.bytes_in.load(Relaxed),
+            bytes_out: self.bytes_out.load(Relaxed),
+            commands_sent: self.commands_sent.load(Relaxed),
+            lines_received: self.lines_received.load(Relaxed),
+            feedback_received: self.feedback_received.load(Relaxed),
+            parse_failures: self.parse_failures.load(Relaxed),
+            corrupted_frames: self.framing.corrupted_frames.load(Relaxed),
+            overflows: self.framing.overflows.load(Relaxed),
+            invalid_utf8: self.framing.invalid_utf8.load(Relaxed),
+            lagged: self.lagged.load(Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum LinkHealth {
+    Good,
+    /// 上个周期内有错误或应答延迟过高
+    Degraded,
+    Down,
+}
+
+/// 每秒速率
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct LinkRates {
+    pub bytes_in: f32,
+    pub bytes_out: f32,
+    pub commands: f32,
+    pub lines: f32,
+    pub feedback: f32,
+    pub errors: f32,
+}
+
+/// 链路状况报告，用于前端的链路指示
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkReport {
+    pub health: LinkHealth,
+    pub totals: LinkCounters,
+    pub rates: LinkRates,
+    pub latency: LatencyStats,
+}
+
+impl LinkReport {
+    /// 根据两次快照计算，`elapsed` 为两次快照的间隔
+    pub fn new(connected: bool, totals: LinkCounters, previous: &LinkCounters, elapsed: Duration, latency: LatencyStats) -> Self {
+        let secs = elapsed.as_secs_f32().max(f32::EPSILON);
+        let rate = |now: u64, before: u64| now.saturating_sub(before) as f32 / secs;
+        let rates = LinkRates {
+            bytes_in: rate(totals.bytes_in, previous.bytes_in),
+            bytes_out: rate(totals.bytes_out, previous.bytes_out),
+            commands: rate(totals.commands_sent, previous.commands_sent),
+            lines: rate(totals.lines_received, previous.lines_received),
+            feedback: rate(totals.feedback_received, previous.feedback_received),
+            errors: rate(totals.errors(), previous.errors()),
+        };
+        let health = if !connected {
+            LinkHealth::Down
+        } else if rates.errors > 0f32 || latency.last_ms > LATENCY_DEGRADED.as_secs_f32() * 1000f32 {
+            LinkHealth::Degraded
+        } else {
+            LinkHealth::Good
+        };
+        Self { health, totals, rates, latency }
+    }
+}
@@ -1,22 +1,54 @@
-use crate::calibration_parser::CalibrationParser;
-use crate::command::{MotorCalibrationCommand, MotorConfigCommand, MotorConfigSave, MotorFeedbackCommand, MotorRunCommand, MotorState};
-use crate::config_parser::{ConfigParser, MotorConfig};
+use crate::calibration_parser::receive_calibration;
+use crate::command::{MotorCalibrationCommand, MotorConfigCommand, MotorConfigSave, MotorFaultCommand, MotorFeedbackCommand, MotorRunCommand, MotorState, STATEFUL_COMMANDS};
+use crate::config_parser::{receive_config, MotorConfig};
+use crate::device_log::DeviceLog;
 use crate::error::MotorError;
+use crate::events::{EventSink, MotorEvent};
 use crate::exit_signal::ExitSignal;
+use crate::fault_parser::{parse_fault, FaultCode, FaultRecord};
+use crate::feedback_parser::{is_feedback_line, looks_like_feedback, parse_feedback, parse_state, FeedbackValue};
+use crate::firmware_image::FirmwareImage;
+use crate::flasher::flash_firmware;
+use crate::frame::{Protocol, PROTOCOL_BINARY_ACK, PROTOCOL_TEXT_ACK};
+use crate::link_stats::LinkReport;
+use crate::safety::SafetyLimits;
 use crate::serial::SerialDevice;
-use log::{error, warn};
-use scan_fmt::scan_fmt;
+use crate::version_parser::{receive_version, FirmwareInfo};
+use crate::watchdog::{HostHeartbeat, FIRMWARE_TIMEOUT, KEEPALIVE_PERIOD};
+use crate::waveform::SetpointTarget;
+use log::{debug, warn};
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::AtomicBool;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
 use std::sync::atomic::Ordering::Relaxed;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
-pub use tauri::{AppHandle, Emitter, Manager};
 use tokio::select;
-use tokio::sync::Mutex;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, watch, Mutex};
 use tokio::task::JoinHandle;
-use tokio::time::{timeout, Duration};
+use tokio::time::{interval, timeout, Duration, Instant, MissedTickBehavior};
 // const MAX_HISTORY: usize = 100000;
+/// 故障记录的最大条数
+const FAULT_LOG_LIMIT: usize = 100;
+
+/// 周期同步下位机状态的间隔
+pub const STATE_SYNC_PERIOD: Duration = Duration::from_secs(2);
+
+/// 链路状况上报间隔
+pub const LINK_STATS_PERIOD: Duration = Duration::from_secs(1);
+
+/// 不等待应答的指令（保存、配置、原始指令等），发送后该时间内收到的行视为其应答
+const REPLY_WINDOW: Duration = Duration::from_millis(300);
+
+/// 等待应答期间持有，释放时减少 `Motor::pending_replies`
+struct ReplyGuard(Arc<AtomicUsize>);
+
+impl Drop for ReplyGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Relaxed);
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Timestamped<T> {
@@ -36,6 +68,30 @@ impl<T> Timestamped<T> {
 }
 
 
+/// 直接通过串口查询下位机状态，不需要 Motor 的锁
+pub async fn query_serial_state(serial: &SerialDevice) -> Result<MotorState, MotorError> {
+    let mut rx = serial.recv_event_tx.subscribe();
+    serial.send(&MotorFeedbackCommand::GetState.to_string()).await.map_err(MotorError::SerialError)?;
+    let sent_at = Instant::now();
+    let reply = timeout(Duration::from_millis(500), async {
+        loop {
+            match rx.recv().await {
+                Ok(line) if line == "__DISCONNECTED__" => return Err(MotorError::Disconnected),
+                Ok(line) => if let Some(state) = parse_state(&line) {
+                    serial.stats.record_latency(sent_at.elapsed());
+                    return Ok(state);
+                },
+                Err(RecvError::Lagged(n)) => {
+                    serial.stats.lagged.fetch_add(n, Relaxed);
+                    continue;
+                }
+                Err(RecvError::Closed) => return Err(MotorError::Disconnected),
+            }
+        }
+    }).await;
+    reply.unwrap_or(Err(MotorError::Timeout))
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MotorFeedbackState {
     None,
@@ -48,14 +104,37 @@ pub enum MotorFeedbackState {
 #[derive(Debug)]
 pub struct Motor {
     pub serial: Arc<SerialDevice>,
-    pub state: Mutex<MotorState>,
+    /// 电机状态，读取不需要等待锁，长时间操作期间也能随时读取
+    state: watch::Sender<MotorState>,
+    /// 串行化会改变状态的指令，只在检查状态和发送期间持有，不跨越长时间等待
+    command_lock: Mutex<()>,
     pub feedback: Mutex<MotorFeedbackState>,
     pub motor_config: Mutex<Option<MotorConfig>>,
-    pub app: AppHandle,
+    /// 软件安全限制，仅对当前连接有效
+    pub safety: Mutex<SafetyLimits>,
+    /// 上一次下发的设定值，用于限制变化率，停机后清空
+    last_setpoint: Mutex<Option<(SetpointTarget, f32, Instant)>>,
+    /// 故障记录，最新的在最后
+    pub fault_log: Mutex<VecDeque<FaultRecord>>,
+    /// 连接时查询到的固件信息
+    pub firmware: Mutex<Option<FirmwareInfo>>,
+    pub events: Arc<dyn EventSink>,
     pub unsaved: AtomicBool, // 是否有未保存的配置
+    /// 断开时等待自动重连，而不是直接通知断开
+    pub auto_reconnect: AtomicBool,
+    /// 解析后的反馈数据广播
+    pub feedback_tx: broadcast::Sender<Timestamped<FeedbackValue>>,
+    /// 正在等待应答的请求数，期间收到的非故障消息是应答，不作为设备日志
+    pending_replies: Arc<AtomicUsize>,
 
     parser_feedback_handle: Mutex<Option<JoinHandle<()>>>,
     parser_feedback_exit_signal: Arc<ExitSignal>,
+    setpoint_stream: Mutex<Option<(JoinHandle<()>, Arc<ExitSignal>)>>,
+    state_sync: Mutex<Option<(JoinHandle<()>, Arc<ExitSignal>)>>,
+    keepalive: Mutex<Option<(JoinHandle<()>, Arc<ExitSignal>)>>,
+    link_monitor: Mutex<Option<(JoinHandle<()>, Arc<ExitSignal>)>>,
+    /// 最近一次周期上报的链路状况
+    link_report: Mutex<Option<LinkReport>>,
 
     // pub speed_history: Mutex<VecDeque<Timestamped<f32>>>,
     // pub position_history: Mutex<VecDeque<Timestamped<f32>>>,
@@ -63,26 +142,127 @@ pub struct Motor {
     // pub udc_history: Mutex<VecDeque<Timestamped<f32>>>,
 }
 impl Motor {
-    pub fn new(serial: Arc<SerialDevice>, app: AppHandle) -> Arc<Self> {
+    pub fn new(serial: Arc<SerialDevice>, events: Arc<dyn EventSink>) -> Arc<Self> {
+        let (feedback_tx, _) = broadcast::channel(1024);
         Arc::new(Self {
             serial,
-            state: Mutex::new(MotorState::Stop),
+            state: watch::Sender::new(MotorState::Stop),
+            command_lock: Mutex::new(()),
             feedback: Mutex::new(MotorFeedbackState::None),
             motor_config: Mutex::new(None),
-            app,
+            safety: Default::default(),
+            last_setpoint: Default::default(),
+            fault_log: Default::default(),
+            firmware: Mutex::new(None),
+            events,
             // speed_history: Mutex::new(VecDeque::with_capacity(MAX_HISTORY)),
             // position_history: Mutex::new(VecDeque::with_capacity(MAX_HISTORY)),
             // current_history: Mutex::new(VecDeque::with_capacity(MAX_HISTORY)),
             // udc_history: Mutex::new(VecDeque::with_capacity(MAX_HISTORY)),
             unsaved: AtomicBool::new(false),
+            auto_reconnect: AtomicBool::new(false),
+            feedback_tx,
+            pending_replies: Default::default(),
             parser_feedback_handle: Default::default(),
             parser_feedback_exit_signal: ExitSignal::new(),
+            setpoint_stream: Default::default(),
+            state_sync: Default::default(),
+            keepalive: Default::default(),
+            link_monitor: Default::default(),
+            link_report: Default::default(),
         })
     }
 
+    /// 在已打开的串口上建立会话：复位下位机协议、查询固件、对齐状态、协商协议并启动后台任务
+    pub async fn open(serial: Arc<SerialDevice>, events: Arc<dyn EventSink>, heartbeat: Option<Arc<HostHeartbeat>>) -> Result<Arc<Self>, MotorError> {
+        if let Err(e) = serial.reset_remote_protocol().await {
+            warn!("Failed to reset protocol: {e}");
+        }
+        let motor = Self::new(serial, events);
+        motor.start_parse_feedback_loop().await;
+        // 查询固件能力，用于限制旧固件不支持的配置
+        if let Err(e) = motor.query_firmware().await {
+            let _ = motor.close().await;
+            return Err(e);
+        }
+        // 连接时板子可能已在运行，以实际状态为准
+        if let Err(e) = motor.sync_state().await {
+            warn!("Failed to query motor state: {e}");
+        }
+        // 固件支持时使用二进制协议，失败时保持文本协议
+        match motor.negotiate_protocol().await {
+            Ok(protocol) => debug!("{} using {protocol} protocol", motor.serial.port_name),
+            Err(e) => warn!("Failed to negotiate protocol: {e}"),
+        }
+        motor.start_state_sync(STATE_SYNC_PERIOD).await;
+        motor.start_link_monitor(LINK_STATS_PERIOD).await;
+        motor.start_keepalive(heartbeat).await;
+        Ok(motor)
+    }
+
+    /// 停止所有会写串口的后台任务
+    pub async fn stop_background_tasks(self: &Arc<Self>) {
+        self.stop_setpoint_stream().await;
+        self.stop_state_sync().await;
+        self.stop_keepalive().await;
+        self.stop_link_monitor().await;
+    }
+
+    /// 停止后台任务，切换回文本协议并关闭串口
+    pub async fn close(self: &Arc<Self>) -> Result<(), MotorError> {
+        self.stop_background_tasks().await;
+        if self.serial.connected.load(Relaxed) {
+            if let Err(e) = self.restore_text_protocol().await {
+                warn!("Failed to restore text protocol: {e}");
+            }
+        }
+        self.serial.disconnect().await.map_err(MotorError::SerialError)?;
+        // 等待 parse loop 停止
+        self.stop_parse_feedback_loop().await;
+        Ok(())
+    }
+
+    /// 烧录固件。bootloader 对未知指令应答 `err`，烧录前先停止所有会写串口的后台任务
+    pub async fn flash_firmware(self: &Arc<Self>, image: &FirmwareImage) -> Result<(), MotorError> {
+        self.stop_background_tasks().await;
+        self.set_feedback(MotorFeedbackState::None).await?;
+        flash_firmware(&self.serial, image, self.events.as_ref()).await
+    }
+
+    pub fn state(&self) -> MotorState {
+        *self.state.borrow()
+    }
+
+    /// 订阅状态变化
+    pub fn subscribe_state(&self) -> watch::Receiver<MotorState> {
+        self.state.subscribe()
+    }
+
+    /// 在返回值释放前收到的行视为应答
+    fn expect_reply(&self) -> ReplyGuard {
+        self.pending_replies.fetch_add(1, Relaxed);
+        ReplyGuard(Arc::clone(&self.pending_replies))
+    }
+
+    /// 不等待应答的指令，在 `REPLY_WINDOW` 内收到的行视为应答
+    fn expect_reply_shortly(&self) {
+        let guard = self.expect_reply();
+        tokio::spawn(async move {
+            tokio::time::sleep(REPLY_WINDOW).await;
+            drop(guard);
+        });
+    }
+
+    /// 更新状态，变化时通知前端
+    fn set_state(&self, state: MotorState) {
+        if self.state.send_replace(state) != state {
+            self.events.emit(MotorEvent::StateChanged(state));
+        }
+    }
+
     async fn send_command(self: &Arc<Self>, cmd: String) -> Result<(), MotorError> {
-        self.app.emit("serial-sent", &cmd).unwrap();
-        self.serial.send(cmd.as_str()).await.map_err(|e| MotorError::SerialError(e))
+        self.events.emit(MotorEvent::SerialSent(cmd.clone()));
+        self.serial.send(cmd.as_str()).await.map_err(MotorError::SerialError)
     }
 
     pub async fn set_feedback(self: &Arc<Self>, new_feedback: MotorFeedbackState) -> Result<(), MotorError> {
@@ -109,86 +289,571 @@ impl Motor {
     /// 从下位机加载 config 并返回
     pub async fn load_config(self: &Arc<Self>) -> Result<MotorConfig, MotorError> {
         let mut feedback = self.feedback.lock().await;
+        let _reply = self.expect_reply();
+        let mut rx = self.serial.recv_event_tx.subscribe();
         let cmd = MotorFeedbackCommand::GetConfig.to_string();
         self.send_command(cmd).await?;
         *feedback = MotorFeedbackState::None;
         // 发送之后立即释放 feedback
         drop(feedback);
-        let mut rx = self.serial.recv_event_tx.subscribe();
-        // 待解析的数据
-        let mut config_parser = ConfigParser::default();
-        let mut section = String::new();
-        let duration = Duration::from_secs(5);
-        let result = timeout(duration, async {
-            while let Ok(line) = rx.recv().await {
-                config_parser.parse_line(&line, &mut section);
-
-                if config_parser.is_complete() {
+        let sent_at = Instant::now();
+        let motor_config = receive_config(&mut rx, &self.serial.stats, Duration::from_secs(5)).await?;
+        self.serial.stats.record_latency(sent_at.elapsed());
+        *self.motor_config.lock().await = Some(motor_config.clone());
+        Ok(motor_config)
+    }
+
+    pub async fn send_running_command(self: &Arc<Self>, run_cmd: &MotorRunCommand) -> Result<(), MotorError> {
+        // 手动下发的运行指令优先于设定值流
+        self.stop_setpoint_stream().await;
+        self.send_run_command(run_cmd).await
+    }
+
+    async fn send_run_command(self: &Arc<Self>, run_cmd: &MotorRunCommand) -> Result<(), MotorError> {
+        // 急停锁存期间只允许 stop，设定值流、扫描和脚本会因此退出
+        if self.serial.emergency_stopped.load(Relaxed) && !matches!(run_cmd, MotorRunCommand::Stop) {
+            return Err(MotorError::EmergencyStopped);
+        }
+        let _guard = self.command_lock.lock().await;
+        if self.serial.emergency_stopped.load(Relaxed) && !matches!(run_cmd, MotorRunCommand::Stop) {
+            return Err(MotorError::EmergencyStopped);
+        }
+        let previous = self.state();
+        // 故障状态下只允许 stop，且 stop 不清除故障
+        if previous == MotorState::Fault {
+            return match run_cmd {
+                MotorRunCommand::Stop => self.send_command(MotorRunCommand::Stop.to_string(&previous).unwrap()).await,
+                _ => Err(self.fault_detected().await),
+            };
+        }
+        let setpoint = match *run_cmd {
+            MotorRunCommand::SetSpeed(v) => Some((SetpointTarget::Speed, v)),
+            MotorRunCommand::SetPosition(v) => Some((SetpointTarget::Position, v)),
+            MotorRunCommand::Stop => None,
+        };
+        if let Some((target, value)) = setpoint {
+            self.check_setpoint(previous, target, value).await?;
+        }
+        let Some(line) = run_cmd.to_string(&previous) else {
+            return Err(MotorError::invalid_state(previous, "send run command"));
+        };
+        if matches!(run_cmd, MotorRunCommand::Stop) {
+            self.send_command(line).await?;
+        } else {
+            // 等待指令锁期间可能已急停，写锁内再检查一次，急停之后不会再写入
+            if !self.serial.send_unless_stopped(&line).await.map_err(MotorError::SerialError)? {
+                return Err(MotorError::EmergencyStopped);
+            }
+            self.events.emit(MotorEvent::SerialSent(line));
+        }
+        // 更新电机状态，Feedback 状态
+        let (state, feedback) = match run_cmd {
+            MotorRunCommand::Stop => (MotorState::Stop, MotorFeedbackState::None),
+            MotorRunCommand::SetPosition(_) => (MotorState::DebugRun, MotorFeedbackState::Position),
+            MotorRunCommand::SetSpeed(_) => (MotorState::DebugRun, MotorFeedbackState::Speed),
+        };
+        // 故障和急停不等待指令锁，检查之后它们写入的状态不能被覆盖
+        let mut preempted = None;
+        let changed = self.state.send_if_modified(|current| {
+            if *current != previous {
+                preempted = Some(*current);
+                return false;
+            }
+            if state != MotorState::Stop && self.serial.emergency_stopped.load(Relaxed) {
+                preempted = Some(*current);
+                return false;
+            }
+            *current = state;
+            state != previous
+        });
+        match preempted {
+            None => {}
+            Some(_) if matches!(run_cmd, MotorRunCommand::Stop) => return Ok(()),
+            Some(MotorState::Fault) => return Err(self.fault_detected().await),
+            Some(_) => return Err(MotorError::EmergencyStopped),
+        }
+        *self.last_setpoint.lock().await = setpoint.map(|(target, value)| (target, value, Instant::now()));
+        *self.feedback.lock().await = feedback;
+        // 设定值流会高频下发指令，只在状态变化时同步给前端；校准中收到 stop 会由此打断校准
+        if changed {
+            self.events.emit(MotorEvent::StateChanged(state));
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn fault_detected(&self) -> MotorError {
+        let description = self.fault_log.lock().await.back()
+            .map(|f| f.description.clone())
+            .unwrap_or_default();
+        MotorError::FaultDetected(description)
+    }
+
+    /// 以固定频率持续下发设定值，`setpoint` 以启动后经过的秒数为参数，返回 None 时结束并停机
+    ///
+    /// 手动下发运行指令、断开连接、故障或下发失败时退出，返回的信号在退出后触发
+    pub async fn start_setpoint_stream<F>(self: &Arc<Self>, target: SetpointTarget, rate_hz: f32, mut setpoint: F) -> Result<Arc<ExitSignal>, MotorError>
+    where
+        F: FnMut(f32) -> Option<f32> + Send + 'static,
+    {
+        if !(rate_hz > 0f32 && rate_hz <= 1000f32) {
+            return Err(MotorError::InvalidArgument("update rate must be in (0, 1000] Hz".into()));
+        }
+        self.stop_setpoint_stream().await;
+
+        let exit_signal = ExitSignal::new();
+        let finished = ExitSignal::new();
+        let signal = Arc::clone(&exit_signal);
+        let done = Arc::clone(&finished);
+        let this = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            let mut ticker = interval(Duration::from_secs_f32(1f32 / rate_hz));
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            let start = Instant::now();
+            loop {
+                select! {
+                    _ = ticker.tick() => {}
+                    _ = signal.wait() => break,
+                }
+                let Some(value) = setpoint(start.elapsed().as_secs_f32()) else {
+                    // 正常结束后停机
+                    if let Err(e) = this.send_run_command(&MotorRunCommand::Stop).await {
+                        warn!("Failed to stop after setpoint stream: {e}");
+                    }
+                    break;
+                };
+                let cmd = match target {
+                    SetpointTarget::Speed => MotorRunCommand::SetSpeed(value),
+                    SetpointTarget::Position => MotorRunCommand::SetPosition(value),
+                };
+                if let Err(e) = this.send_run_command(&cmd).await {
+                    warn!("Setpoint stream stopped: {e}");
                     break;
                 }
             }
-        }).await;
-        match result {
-            Ok(_) =>
-                match config_parser.try_into_motor_config() {
-                    Ok(motor_config) => {
-                        let mut config = self.motor_config.lock().await;
-                        *config = Some(motor_config.clone());
-                        Ok(motor_config)
+            done.trigger();
+            this.events.emit(MotorEvent::SetpointStreamStopped);
+        });
+        *self.setpoint_stream.lock().await = Some((handle, exit_signal));
+        Ok(finished)
+    }
+
+    pub async fn stop_setpoint_stream(self: &Arc<Self>) {
+        if let Some((handle, exit_signal)) = self.setpoint_stream.lock().await.take() {
+            exit_signal.trigger();
+            let _ = handle.await;
+        }
+    }
+
+    /// 向下位机查询实际状态，周期查询不显示在控制台
+    pub async fn query_state(self: &Arc<Self>) -> Result<MotorState, MotorError> {
+        query_serial_state(&self.serial).await
+    }
+
+    /// 查询实际状态并与本地记录的状态对齐，不一致时以下位机为准。
+    /// 固件不支持 `get_state` 时直接返回本地状态；本地故障锁存到 `clear_fault`，不参与对齐
+    pub async fn sync_state(self: &Arc<Self>) -> Result<MotorState, MotorError> {
+        let supported = self.firmware.lock().await.as_ref().is_some_and(|f| f.supports("get_state"));
+        let local = self.state();
+        if !supported || local == MotorState::Fault {
+            return Ok(local);
+        }
+        let device = self.query_state().await?;
+        let _guard = self.command_lock.lock().await;
+        let state = self.state();
+        // 查询期间本地状态已被指令修改，以指令为准，等下一次同步
+        if state != local || state == device {
+            return Ok(state);
+        }
+        warn!("Motor state mismatch: local {local}, device {device}");
+        if device != MotorState::DebugRun && device != MotorState::Run {
+            *self.feedback.lock().await = MotorFeedbackState::None;
+        }
+        self.set_state(device);
+        Ok(device)
+    }
+
+    /// 周期同步下位机状态
+    pub async fn start_state_sync(self: &Arc<Self>, period: Duration) {
+        self.stop_state_sync().await;
+        let exit_signal = ExitSignal::new();
+        let signal = Arc::clone(&exit_signal);
+        let this = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            let mut ticker = interval(period);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                select! {
+                    _ = ticker.tick() => {}
+                    _ = signal.wait() => break,
+                }
+                // 校准过程中不打扰下位机
+                if this.state() == MotorState::Test || !this.serial.connected.load(Relaxed) {
+                    continue;
+                }
+                if let Err(e) = this.sync_state().await {
+                    warn!("Failed to sync motor state: {e}");
+                }
+            }
+        });
+        *self.state_sync.lock().await = Some((handle, exit_signal));
+    }
+
+    pub async fn stop_state_sync(self: &Arc<Self>) {
+        if let Some((handle, exit_signal)) = self.state_sync.lock().await.take() {
+            exit_signal.trigger();
+            let _ = handle.await;
+        }
+    }
+
+    /// DebugRun 期间周期发送 keepalive，下位机超时自行停机；
+    /// 指定前端心跳时，前端停止心跳后由上位机发送 stop
+    pub async fn start_keepalive(self: &Arc<Self>, heartbeat: Option<Arc<HostHeartbeat>>) {
+        self.stop_keepalive().await;
+        let exit_signal = ExitSignal::new();
+        let signal = Arc::clone(&exit_signal);
+        let this = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            let keepalive = MotorFeedbackCommand::Keepalive { timeout_ms: FIRMWARE_TIMEOUT.as_millis() as u64 }.to_string();
+            let mut ticker = interval(KEEPALIVE_PERIOD);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                select! {
+                    _ = ticker.tick() => {}
+                    _ = signal.wait() => break,
+                }
+                if this.state() != MotorState::DebugRun {
+                    continue;
+                }
+                if heartbeat.as_ref().is_some_and(|h| h.is_expired()) {
+                    warn!("Host heartbeat lost, stopping motor");
+                    match this.send_running_command(&MotorRunCommand::Stop).await {
+                        Ok(()) => this.events.emit(MotorEvent::WatchdogTripped),
+                        Err(e) => warn!("Watchdog failed to stop motor: {e}"),
                     }
-                    Err(e) => Err(MotorError::ParseError(e.to_string()))
-                },
-            Err(_) => Err(MotorError::Timeout),
+                    continue;
+                }
+                let supported = this.firmware.lock().await.as_ref().is_some_and(|f| f.supports("keepalive"));
+                // keepalive 频率较高，不显示在控制台
+                if supported {
+                    if let Err(e) = this.serial.send(&keepalive).await {
+                        warn!("Failed to send keepalive: {e}");
+                    }
+                }
+            }
+        });
+        *self.keepalive.lock().await = Some((handle, exit_signal));
+    }
+
+    pub async fn stop_keepalive(self: &Arc<Self>) {
+        if let Some((handle, exit_signal)) = self.keepalive.lock().await.take() {
+            exit_signal.trigger();
+            let _ = handle.await;
         }
     }
 
-    pub async fn send_running_command(self: &Arc<Self>, run_cmd: &MotorRunCommand) -> Result<(), MotorError> {
-        let mut state = self.state.lock().await;
-        if let Some(line) = run_cmd.to_string(&state) {
-            self.send_command(line).await
-        } else {
-            Err(MotorError::InvalidState("cannot send command in current state".into()))
-        }?;
-        // 更新电机状态，Feedback 状态
-        match run_cmd {
-            MotorRunCommand::Stop => {
-                *state = MotorState::Stop;
-                *self.feedback.lock().await = MotorFeedbackState::None;
+    /// 周期统计链路状况并通知前端
+    pub async fn start_link_monitor(self: &Arc<Self>, period: Duration) {
+        self.stop_link_monitor().await;
+        let exit_signal = ExitSignal::new();
+        let signal = Arc::clone(&exit_signal);
+        let this = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            let mut ticker = interval(period);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            let mut previous = this.serial.stats.snapshot();
+            let mut previous_at = Instant::now();
+            loop {
+                select! {
+                    _ = ticker.tick() => {}
+                    _ = signal.wait() => break,
+                }
+                let totals = this.serial.stats.snapshot();
+                let connected = this.serial.connected.load(Relaxed);
+                let report = LinkReport::new(connected, totals, &previous, previous_at.elapsed(), this.serial.stats.latency());
+                previous = totals;
+                previous_at = Instant::now();
+                *this.link_report.lock().await = Some(report.clone());
+                this.events.emit(MotorEvent::LinkStats(report));
+            }
+        });
+        *self.link_monitor.lock().await = Some((handle, exit_signal));
+    }
+
+    pub async fn stop_link_monitor(self: &Arc<Self>) {
+        if let Some((handle, exit_signal)) = self.link_monitor.lock().await.take() {
+            exit_signal.trigger();
+            let _ = handle.await;
+        }
+    }
+
+    /// 最近一次链路状况，尚未周期上报时速率为 0
+    pub async fn link_report(&self) -> LinkReport {
+        if let Some(report) = self.link_report.lock().await.clone() {
+            return report;
+        }
+        let totals = self.serial.stats.snapshot();
+        LinkReport::new(self.serial.connected.load(Relaxed), totals, &totals, Duration::from_secs(1), self.serial.stats.latency())
+    }
+
+    /// 按安全限制检查设定值，超限时拒绝并通知前端
+    async fn check_setpoint(&self, state: MotorState, target: SetpointTarget, value: f32) -> Result<(), MotorError> {
+        let last = match *self.last_setpoint.lock().await {
+            Some((last_target, last_value, at)) if last_target == target => Some((last_value, at.elapsed())),
+            // 从停止状态启动速度环时以 0 为起点
+            _ if state == MotorState::Stop && target == SetpointTarget::Speed => Some((0f32, Duration::MAX)),
+            _ => None,
+        };
+        let result = self.safety.lock().await.check_setpoint(target, value, last);
+        result.map_err(|violation| {
+            let error = MotorError::SafetyLimit(violation.describe());
+            self.events.emit(MotorEvent::SafetyViolation(violation));
+            error
+        })
+    }
+
+    /// 反馈超出安全限制，运行中则自动停机
+    async fn check_feedback(self: &Arc<Self>, value: &FeedbackValue) {
+        let Some(violation) = self.safety.lock().await.check_feedback(value) else {
+            return;
+        };
+        let state = self.state();
+        if state != MotorState::DebugRun && state != MotorState::Run {
+            return;
+        }
+        warn!("Safety limit exceeded: {}", violation.describe());
+        if let Err(e) = self.send_running_command(&MotorRunCommand::Stop).await {
+            warn!("Failed to stop after safety violation: {e}");
+        }
+        self.events.emit(MotorEvent::SafetyViolation(violation));
+    }
+
+    /// 急停后对齐本地状态，不等待指令锁，正在进行的校准会因此中止
+    pub fn mark_emergency_stopped(&self) {
+        let stopped = self.state.send_if_modified(|state| {
+            if *state == MotorState::Stop || *state == MotorState::Fault {
+                return false;
             }
-            MotorRunCommand::SetPosition(_) => {
-                *state = MotorState::DebugRun;
-                *self.feedback.lock().await = MotorFeedbackState::Position;
+            *state = MotorState::Stop;
+            true
+        });
+        if !stopped {
+            return;
+        }
+        if let Ok(mut feedback) = self.feedback.try_lock() {
+            *feedback = MotorFeedbackState::None;
+        }
+        if let Ok(mut last) = self.last_setpoint.try_lock() {
+            *last = None;
+        }
+        self.events.emit(MotorEvent::StateChanged(MotorState::Stop));
+    }
+
+    /// 清除故障，回到 Stop 状态
+    pub async fn clear_fault(self: &Arc<Self>) -> Result<(), MotorError> {
+        let _guard = self.command_lock.lock().await;
+        let state = self.state();
+        let Some(line) = MotorFaultCommand::ClearFault.to_string(&state) else {
+            return Err(MotorError::invalid_state(state, "clear fault"));
+        };
+        self.expect_reply_shortly();
+        self.send_command(line).await?;
+        self.set_state(MotorState::Stop);
+        Ok(())
+    }
+
+    /// 收到故障消息：进入 Fault 状态并记录
+    async fn handle_fault(self: &Arc<Self>, code: FaultCode, line: &str) {
+        // 不等待指令锁，故障需要立即生效，校准和设定值流会因状态变化退出
+        let previous = self.state.send_replace(MotorState::Fault);
+        if previous != MotorState::Fault {
+            self.events.emit(MotorEvent::StateChanged(MotorState::Fault));
+        }
+        let record = FaultRecord::new(code, line, previous);
+        {
+            let mut log = self.fault_log.lock().await;
+            log.push_back(record.clone());
+            if log.len() > FAULT_LOG_LIMIT {
+                log.pop_front();
+            }
+        }
+        self.events.emit(MotorEvent::Fault(record));
+    }
+
+    /// 直接发送一行原始指令，用于调试未在 command.rs 中建模的指令。
+    /// 配置与保存指令与专用命令一样只能在 Stop 状态下发送；返回发送前订阅的接收端，用于等待应答
+    pub async fn send_raw_line(self: &Arc<Self>, line: &str) -> Result<broadcast::Receiver<String>, MotorError> {
+        let line = line.trim();
+        let Some(name) = line.split_whitespace().next() else {
+            return Err(MotorError::InvalidArgument("command is empty".into()));
+        };
+        if STATEFUL_COMMANDS.contains(&name) {
+            return Err(MotorError::InvalidArgument(format!("{name} must be sent with its dedicated command")));
+        }
+        let is_config = name.starts_with("config_");
+        let is_save = name == "save";
+        let _guard = if is_config || is_save { Some(self.command_lock.lock().await) } else { None };
+        let state = self.state();
+        if state == MotorState::Test {
+            return Err(MotorError::invalid_state(state, "send raw command"));
+        }
+        if is_config && state != MotorState::Stop {
+            return Err(MotorError::invalid_state(state, "change config"));
+        }
+        if is_save && MotorConfigSave.to_string(&state).is_none() {
+            return Err(MotorError::invalid_state(state, "save config"));
+        }
+
+        let rx = self.serial.recv_event_tx.subscribe();
+        self.expect_reply_shortly();
+        self.send_command(format!("{line}\r\n")).await?;
+        if is_config {
+            self.unsaved.store(true, Relaxed);
+        } else if is_save {
+            self.unsaved.store(false, Relaxed);
+        }
+        Ok(rx)
+    }
+
+    /// 等待包含 `pattern` 的应答，`rx` 来自 `send_raw_line`
+    pub async fn wait_raw_reply(&self, mut rx: broadcast::Receiver<String>, pattern: &str, wait: Duration) -> Result<String, MotorError> {
+        let _reply = self.expect_reply();
+        let sent_at = Instant::now();
+        let reply = timeout(wait, async {
+            loop {
+                match rx.recv().await {
+                    Ok(reply) if reply == "__DISCONNECTED__" => return Err(MotorError::Disconnected),
+                    Ok(reply) if reply.contains(pattern) => {
+                        self.serial.stats.record_latency(sent_at.elapsed());
+                        return Ok(reply);
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(n)) => {
+                        self.serial.stats.lagged.fetch_add(n, Relaxed);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return Err(MotorError::Disconnected),
+                }
             }
-            MotorRunCommand::SetSpeed(_) => {
-                *state = MotorState::DebugRun;
-                *self.feedback.lock().await = MotorFeedbackState::Speed;
+        }).await;
+        reply.unwrap_or(Err(MotorError::Timeout))
+    }
+
+    /// 查询固件版本与能力，旧固件不应答时按 legacy 处理
+    pub async fn query_firmware(self: &Arc<Self>) -> Result<FirmwareInfo, MotorError> {
+        let _reply = self.expect_reply();
+        let mut rx = self.serial.recv_event_tx.subscribe();
+        self.send_command(MotorFeedbackCommand::GetVersion.to_string()).await?;
+        let sent_at = Instant::now();
+        let info = match receive_version(&mut rx, &self.serial.stats, Duration::from_secs(1)).await {
+            Ok(info) => {
+                self.serial.stats.record_latency(sent_at.elapsed());
+                info
+            }
+            Err(MotorError::Timeout) => FirmwareInfo::legacy(),
+            Err(e) => return Err(e),
+        };
+        *self.firmware.lock().await = Some(info.clone());
+        Ok(info)
+    }
+
+    /// 固件支持时切换到二进制帧协议，不支持或没有应答时保持文本协议
+    pub async fn negotiate_protocol(self: &Arc<Self>) -> Result<Protocol, MotorError> {
+        let supported = self.firmware.lock().await.as_ref().is_some_and(|f| f.supports("protocol"));
+        if !supported {
+            return Ok(Protocol::Text);
+        }
+        let mut rx = self.serial.recv_event_tx.subscribe();
+        self.send_command(MotorFeedbackCommand::SetProtocol(Protocol::Binary).to_string()).await?;
+        if self.wait_protocol_ack(&mut rx, PROTOCOL_BINARY_ACK).await? {
+            Ok(Protocol::Binary)
+        } else {
+            warn!("Firmware did not acknowledge binary protocol, using text protocol");
+            Ok(Protocol::Text)
+        }
+    }
+
+    /// 断开前切换回文本协议，以免下位机停留在二进制协议，其他工具无法再通信
+    pub async fn restore_text_protocol(self: &Arc<Self>) -> Result<(), MotorError> {
+        if self.protocol() == Protocol::Text {
+            return Ok(());
+        }
+        let mut rx = self.serial.recv_event_tx.subscribe();
+        self.send_command(MotorFeedbackCommand::SetProtocol(Protocol::Text).to_string()).await?;
+        if self.wait_protocol_ack(&mut rx, PROTOCOL_TEXT_ACK).await? {
+            Ok(())
+        } else {
+            Err(MotorError::Timeout)
+        }
+    }
+
+    /// 等待协议切换的应答，应答由串口读取任务识别并切换协议，这里只等待结果；超时返回 false
+    async fn wait_protocol_ack(&self, rx: &mut broadcast::Receiver<String>, ack: &str) -> Result<bool, MotorError> {
+        let _reply = self.expect_reply();
+        let reply = timeout(Duration::from_millis(500), async {
+            loop {
+                match rx.recv().await {
+                    Ok(line) if line == ack => return Ok(true),
+                    Ok(line) if line == "__DISCONNECTED__" => return Err(MotorError::Disconnected),
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(n)) => {
+                        self.serial.stats.lagged.fetch_add(n, Relaxed);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return Err(MotorError::Disconnected),
+                }
+            }
+        }).await;
+        reply.unwrap_or(Ok(false))
+    }
+
+    /// 当前使用的通信协议
+    pub fn protocol(&self) -> Protocol {
+        if self.serial.binary.load(Relaxed) { Protocol::Binary } else { Protocol::Text }
+    }
+
+    /// 检查固件是否支持该配置指令，未查询过固件信息时不做限制
+    async fn check_supported(&self, config_cmd: &MotorConfigCommand) -> Result<(), MotorError> {
+        let firmware = self.firmware.lock().await;
+        let Some(info) = firmware.as_ref() else {
+            return Ok(());
+        };
+        let unsupported = |feature: String| MotorError::Unsupported { feature, firmware: info.version.clone() };
+        if !info.supports(config_cmd.name()) {
+            return Err(unsupported(config_cmd.name().to_string()));
+        }
+        if let MotorConfigCommand::ConfigEncoder { encoder_type, .. } = config_cmd {
+            if !info.supports_encoder(encoder_type) {
+                return Err(unsupported(format!("encoder type {encoder_type}")));
             }
         }
-        // 向前端同步电机状态
-        self.app.emit("motor-state-change", *state).unwrap();
         Ok(())
     }
 
     pub async fn send_config_command(self: &Arc<Self>, config_cmd: &MotorConfigCommand) -> Result<(), MotorError> {
-        let state = self.state.lock().await;
+        self.check_supported(config_cmd).await?;
+        let _guard = self.command_lock.lock().await;
+        let state = self.state();
         if let Some(line) = config_cmd.to_string(&state) {
+            self.expect_reply_shortly();
             self.send_command(line).await?;
             self.unsaved.store(true, Relaxed);
             Ok(())
         } else {
-            Err(MotorError::InvalidState("cannot send command in current state".into()))
+            Err(MotorError::invalid_state(state, "change config"))
         }
     }
 
     pub async fn save_config(self: &Arc<Self>) -> Result<(), MotorError> {
         if self.unsaved.load(Relaxed) {
-            let state = self.state.lock().await;
+            let _guard = self.command_lock.lock().await;
+            let state = self.state();
             if let Some(line) = MotorConfigSave.to_string(&state) {
+                self.expect_reply_shortly();
                 self.send_command(line).await?;
                 self.unsaved.store(false, Relaxed);
                 Ok(())
             } else {
-                Err(MotorError::InvalidState("cannot send command in current state".into()))
+                Err(MotorError::invalid_state(state, "save config"))
             }
         } else {
             Ok(())
@@ -196,51 +861,45 @@ impl Motor {
     }
 
     pub async fn calibration(self: &Arc<Self>) -> Result<(), MotorError> {
-        let mut state = self.state.lock().await;
-        if let Some(line) = MotorCalibrationCommand::Calibration.to_string(&state) {
-            self.send_command(line).await?;
-            *state = MotorState::Test;
-            // 向前端同步状态
-            self.app.emit("motor-state-change", MotorState::Test).unwrap();
-            // TODO: 等待校准完成
-            let mut rx = self.serial.recv_event_tx.subscribe();
-            let duration = Duration::from_secs(120);
-            let mut parser = CalibrationParser::new();
-            let result = timeout(duration, async {
-                while let Ok(line) = rx.recv().await {
-                    match parser.parse(&line) {
-                        Ok(_) => {
-                            self.app.emit("calibration-state", &parser.0).unwrap();
-                            if parser.is_done() {
-                                return Ok(());
-                            }
-                        }
-                        Err(e) => {
-                            return Err(MotorError::CalibrationError(e.to_string()))
-                        }
-                    }
-                }
-                Err(MotorError::SerialError("recv error".into()))
-            }).await;
-            // 校准完成后（不管是成功还是失败）回到停止状态
-            *state = MotorState::Stop;
-            // 向前端同步状态
-            self.app.emit("motor-state-change", MotorState::Stop).unwrap();
-            // 不管是否成功都认为有未保存的数据
-            self.unsaved.store(true, Relaxed);
-            match result {
-                Ok(Ok(_)) => {}
-                Ok(Err(e)) => {
-                    return Err(e);
-                }
-                Err(_) => {
-                    return Err(MotorError::Timeout);
-                }
+        if self.serial.emergency_stopped.load(Relaxed) {
+            return Err(MotorError::EmergencyStopped);
+        }
+        let guard = self.command_lock.lock().await;
+        let state = self.state();
+        let Some(line) = MotorCalibrationCommand::Calibration.to_string(&state) else {
+            return Err(MotorError::invalid_state(state, "start calibration"));
+        };
+        let _reply = self.expect_reply();
+        let mut rx = self.serial.recv_event_tx.subscribe();
+        self.send_command(line).await?;
+        // 向前端同步状态
+        self.set_state(MotorState::Test);
+        // 校准期间不持有指令锁，stop、急停或故障改变状态后中止等待
+        drop(guard);
+        let mut state_rx = self.state.subscribe();
+        let duration = Duration::from_secs(120);
+        let result = select! {
+            result = receive_calibration(&mut rx, &self.serial.stats, duration, |progress| {
+                self.events.emit(MotorEvent::CalibrationProgress(progress.clone()));
+            }) => result,
+            _ = state_rx.wait_for(|state| *state != MotorState::Test) => {
+                Err(MotorError::CalibrationError("calibration interrupted".into()))
             }
-            Ok(())
-        } else {
-            Err(MotorError::InvalidState("cannot calibration in current state".into()))
+        };
+        // 校准完成后（不管是成功还是失败）回到停止状态，被打断时状态已由对应操作设置
+        let finished = self.state.send_if_modified(|state| {
+            let testing = *state == MotorState::Test;
+            if testing {
+                *state = MotorState::Stop;
+            }
+            testing
+        });
+        if finished {
+            self.events.emit(MotorEvent::StateChanged(MotorState::Stop));
         }
+        // 不管是否成功都认为有未保存的数据
+        self.unsaved.store(true, Relaxed);
+        result
     }
 
     // pub async fn push_speed(&self, value: f32) {
@@ -268,9 +927,12 @@ impl Motor {
     // }
 
     pub async fn start_parse_feedback_loop(self: &Arc<Self>) {
+        // 在启动任务前订阅，任务开始运行前收到的消息（包括断开通知）不会丢失
+        let rx = self.serial.recv_event_tx.subscribe();
+        let feedback_rx = self.serial.recv_feedback_tx.subscribe();
         let this = Arc::clone(self);
         *self.parser_feedback_handle.lock().await = Some(tokio::spawn(async move {
-            let _ = this.parse_feedback_loop().await;
+            let _ = this.parse_feedback_loop(rx, feedback_rx).await;
         }));
     }
 
@@ -281,8 +943,31 @@ impl Motor {
         }
     }
 
-    async fn parse_feedback_loop(self: Arc<Self>) {
-        let mut rx = self.serial.recv_event_tx.subscribe();
+    async fn handle_feedback(self: &Arc<Self>, value: FeedbackValue) {
+        self.serial.stats.feedback_received.fetch_add(1, Relaxed);
+        let sample = Timestamped::new(value, value.type_name().to_string());
+        self.events.emit(MotorEvent::Feedback(sample.clone()));
+        // 转发给后端内部的订阅者（参数扫描等）
+        let _ = self.feedback_tx.send(sample);
+        self.check_feedback(&value).await;
+    }
+
+    fn notify_disconnected(&self) {
+        if self.auto_reconnect.load(Relaxed) {
+            self.events.emit(MotorEvent::ConnectionLost);
+        } else {
+            self.events.emit(MotorEvent::Disconnected);
+        }
+    }
+
+    /// 丢弃的消息计入链路统计，由链路指示上报
+    fn record_lagged(&self, n: u64) {
+        self.serial.stats.lagged.fetch_add(n, Relaxed);
+        warn!("Feedback parser lagged behind {}, {n} messages dropped", self.serial.port_name);
+    }
+
+    async fn parse_feedback_loop(self: Arc<Self>, mut rx: broadcast::Receiver<String>, mut feedback_rx: broadcast::Receiver<FeedbackValue>) {
+        let mut feedback_closed = false;
 
         // 循环解析串口消息
         loop {
@@ -292,7 +977,16 @@ impl Motor {
                 line_result = rx.recv() => {
                     let line_result = match line_result {
                         Ok(v) => v,
-                        Err(_) => break, // channel 关闭
+                        // 处理不及时丢弃了部分消息，计数后继续解析，断开通知可能也被丢弃
+                        Err(RecvError::Lagged(n)) => {
+                            self.record_lagged(n);
+                            if !self.serial.connected.load(Relaxed) {
+                                self.notify_disconnected();
+                                break;
+                            }
+                            continue;
+                        }
+                        Err(RecvError::Closed) => break,
                     };
 
                     let line = line_result.trim();
@@ -301,64 +995,49 @@ impl Motor {
                     }
 
                     if line == "__DISCONNECTED__" {
-                        self.app.emit("motor-disconnected", ()).unwrap();
+                        self.notify_disconnected();
                         break;
                     }
 
-                    //self.app.emit("serial-received", line).unwrap();
+                    // 状态查询的应答由 query_state 处理
+                    if parse_state(line).is_some() {
+                        continue;
+                    }
+
+                    let fault = parse_fault(line);
+                    if let Some(code) = fault {
+                        self.handle_fault(code, line).await;
+                    }
+
                     let current_feedback = {
                         let fb = self.feedback.lock().await;
                         *fb
                     };
+                    if let Some(value) = parse_feedback(current_feedback, line) {
+                        self.handle_feedback(value).await;
+                        continue;
+                    }
                     // 由于 feedback 频率太高，会导致前端收到数据太多爆满，串口只回传非反馈信息
-                    if current_feedback == MotorFeedbackState::None {
-                        self.app.emit("serial-received", line).unwrap();
+                    if current_feedback != MotorFeedbackState::None && is_feedback_line(line) {
+                        continue;
                     }
-
-                    match current_feedback {
-                        MotorFeedbackState::Speed => {
-                            if let Ok(speed) = scan_fmt!(line, "speed: {}", f32) {
-                                if let Err(e) = self.app.emit("motor_feedback_update", Timestamped::new(speed, "speed".to_string())) {
-                                    error!("Tauri emit error {e}");
-                                }
-                            } else {
-                                warn!("Failed to parse speed feedback: {}", line);
-                                continue;
-                            }
-                        }
-                        MotorFeedbackState::Position => {
-                            if let Ok(position) = scan_fmt!(line, "position: {}", f32) {
-                                if let Err(e) = self.app.emit("motor_feedback_update", Timestamped::new(position, "position".to_string())) {
-                                    error!("Tauri emit error {e}");
-                                }
-                            } else {
-                                warn!("Failed to parse position feedback: {}", line);
-                                continue;
-                            }
-                        }
-                        MotorFeedbackState::Current => {
-                            if let Ok((ia, ib, ic)) = scan_fmt!(line, "iabc:{},{},{}", f32, f32, f32) {
-                                if let Err(e) = self.app.emit("motor_feedback_update", Timestamped::new((ia, ib, ic), "iabc".to_string())) {
-                                    error!("Tauri emit error {e}");
-                                }
-                            } else {
-                                warn!("Failed to parse current feedback: {}", line);
-                                continue;
-                            }
-                        }
-                        MotorFeedbackState::Udc => {
-                            if let Ok(udc) = scan_fmt!(line, "udc: {}", f32) {
-                                if let Err(e) = self.app.emit("motor_feedback_update", Timestamped::new(udc, "udc".to_string())) {
-                                    error!("Tauri emit error {e}");
-                                }
-                            } else {
-                                warn!("Failed to parse udc feedback: {}", line);
-                                continue;
-                            }
-                        }
-                        MotorFeedbackState::None => { continue; }
+                    // 噪声导致的反馈数据损坏，仍转发到控制台便于排查
+                    if looks_like_feedback(line) {
+                        self.serial.stats.parse_failures.fetch_add(1, Relaxed);
+                    }
+                    // 非反馈消息（告警、故障、应答）在反馈过程中也要转发
+                    self.events.emit(MotorEvent::SerialReceived(line.to_string()));
+                    // 请求的应答（配置、校准进度等）不是设备日志，故障随时可能发生，始终记录
+                    if fault.is_some() || self.pending_replies.load(Relaxed) == 0 {
+                        self.events.emit(MotorEvent::DeviceLog(DeviceLog::new(line)));
                     }
                 }
+                // 二进制协议的反馈已在帧中带有类型，无需解析
+                result = feedback_rx.recv(), if !feedback_closed => match result {
+                    Ok(value) => self.handle_feedback(value).await,
+                    Err(RecvError::Lagged(n)) => self.record_lagged(n),
+                    Err(RecvError::Closed) => feedback_closed = true,
+                },
                 _ = self.parser_feedback_exit_signal.wait() => return,
             }
         }
@@ -0,0 +1,58 @@
+use crate::command::MotorFeedbackCommand;
+use crate::config_parser::{receive_config, MotorConfig};
+use crate::error::MotorError;
+use crate::serial::SerialDevice;
+use log::debug;
+use serde::Serialize;
+use tokio::time::Duration;
+
+/// 依次尝试的波特率，默认值放在最前面
+pub const DEFAULT_BAUD_RATES: &[u32] = &[115200, 921600, 460800, 230400, 57600, 38400, 19200, 9600];
+
+/// 单个波特率等待配置返回的时间
+pub const PROBE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeResult {
+    pub port_name: String,
+    /// 电调应答的波特率，None 表示没有找到电调
+    pub baud_rate: Option<u32>,
+    pub config: Option<MotorConfig>,
+    /// 已尝试的波特率
+    pub tried: Vec<u32>,
+}
+
+/// 在指定串口上依次尝试各个波特率，发送 `get_config` 并等待完整的配置返回
+pub async fn probe_port(port_name: &str, baud_rates: &[u32], per_rate: Duration) -> Result<ProbeResult, MotorError> {
+    let mut result = ProbeResult {
+        port_name: port_name.to_string(),
+        baud_rate: None,
+        config: None,
+        tried: Vec::new(),
+    };
+    for &baud_rate in baud_rates {
+        result.tried.push(baud_rate);
+        let serial = SerialDevice::new(port_name.to_string(), baud_rate);
+        // 串口无法打开时不再尝试其他波特率
+        serial.connect().await.map_err(MotorError::SerialError)?;
+        let mut rx = serial.recv_event_tx.subscribe();
+        // 下位机可能停留在上次会话的二进制协议，先复位再查询
+        if let Err(e) = serial.reset_remote_protocol().await {
+            debug!("probe {port_name} at {baud_rate}: reset protocol failed: {e}");
+        }
+        let answer = match serial.send(&MotorFeedbackCommand::GetConfig.to_string()).await {
+            Ok(()) => receive_config(&mut rx, &serial.stats, per_rate).await,
+            Err(e) => Err(MotorError::SerialError(e)),
+        };
+        let _ = serial.disconnect().await;
+        match answer {
+            Ok(config) => {
+                result.baud_rate = Some(baud_rate);
+                result.config = Some(config);
+                break;
+            }
+            Err(e) => debug!("probe {port_name} at {baud_rate}: {e}"),
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use tokio::time::{Duration, Instant};
+use tokio_serial::{SerialPortInfo, SerialPortType};
+
+/// 自动重连策略
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ReconnectPolicy {
+    pub enabled: bool,
+    /// 超过该时间仍未找到设备则放弃重连
+    pub timeout_secs: u64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self { enabled: false, timeout_secs: 30 }
+    }
+}
+
+/// 通过 USB 信息识别同一个设备，重新插拔后端口名可能变化
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsbIdentity {
+    pub vid: u16,
+    pub pid: u16,
+    pub serial_number: Option<String>,
+}
+
+impl UsbIdentity {
+    pub fn of(port: &SerialPortInfo) -> Option<Self> {
+        match &port.port_type {
+            SerialPortType::UsbPort(usb) => Some(Self {
+                vid: usb.vid,
+                pid: usb.pid,
+                serial_number: usb.serial_number.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// 查找端口对应的 USB 信息
+    pub fn lookup(port_name: &str) -> Option<Self> {
+        tokio_serial::available_ports().ok()?
+            .iter()
+            .find(|p| p.port_name == port_name)
+            .and_then(Self::of)
+    }
+
+    /// 在端口列表中查找同一设备
+    pub fn find_in<'a>(&self, ports: &'a [SerialPortInfo]) -> Option<&'a str> {
+        ports.iter()
+            .find(|p| Self::of(p).as_ref() == Some(self))
+            .map(|p| p.port_name.as_str())
+    }
+}
+
+/// 当前连接的重连信息
+#[derive(Debug, Default)]
+pub struct ReconnectState {
+    pub policy: ReconnectPolicy,
+    /// 当前连接设备的 USB 信息，非 USB 串口无法重连
+    pub identity: Option<UsbIdentity>,
+    /// 连接断开的时间，None 表示连接正常
+    pub lost_at: Option<Instant>,
+}
+
+impl ReconnectState {
+    pub fn can_reconnect(&self) -> bool {
+        self.policy.enabled && self.identity.is_some()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.lost_at.is_some_and(|t| t.elapsed() > Duration::from_secs(self.policy.timeout_secs))
+    }
+}
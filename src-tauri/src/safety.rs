@@ -0,0 +1,144 @@
+use crate::feedback_parser::FeedbackValue;
+use crate::waveform::SetpointTarget;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+
+/// 手动指令之间间隔较长，变化率按不超过该时间计算，相当于限制单次设定值的跳变
+const MAX_SLEW_WINDOW: Duration = Duration::from_secs(1);
+
+/// 软件安全限制，None 表示不限制
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SafetyLimits {
+    /// 速度绝对值上限
+    pub max_speed: Option<f32>,
+    /// 位置窗口
+    pub min_position: Option<f32>,
+    pub max_position: Option<f32>,
+    /// 设定值变化率上限，单位/秒
+    pub max_slew_rate: Option<f32>,
+    /// 相电流绝对值上限
+    pub max_current: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SafetyKind {
+    Speed,
+    Position,
+    SlewRate,
+    Current,
+    /// 设定值为 NaN 或无穷大，与限制无关
+    NotFinite,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SafetyAction {
+    /// 拒绝下发指令
+    Rejected,
+    /// 反馈超限，已自动停机
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SafetyViolation {
+    pub kind: SafetyKind,
+    pub value: f32,
+    pub limit: f32,
+    pub action: SafetyAction,
+}
+
+impl SafetyViolation {
+    fn new(kind: SafetyKind, value: f32, limit: f32, action: SafetyAction) -> Self {
+        Self { kind, value, limit, action }
+    }
+
+    pub fn describe(&self) -> String {
+        if self.kind == SafetyKind::NotFinite {
+            return format!("setpoint {} is not a finite number", self.value);
+        }
+        format!("{:?} {} exceeds limit {}", self.kind, self.value, self.limit)
+    }
+}
+
+impl SafetyLimits {
+    /// 检查限制本身是否有效：所有值必须是有限数，速度、变化率、电流上限不能为负，位置窗口不能颠倒
+    pub fn validate(&self) -> Result<(), String> {
+        let values = [
+            ("max_speed", self.max_speed),
+            ("min_position", self.min_position),
+            ("max_position", self.max_position),
+            ("max_slew_rate", self.max_slew_rate),
+            ("max_current", self.max_current),
+        ];
+        for (name, value) in values {
+            let Some(value) = value else { continue };
+            if !value.is_finite() {
+                return Err(format!("{name} must be a finite number, got {value}"));
+            }
+            if value < 0.0 && !name.ends_with("_position") {
+                return Err(format!("{name} must not be negative, got {value}"));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_position, self.max_position) {
+            if min > max {
+                return Err(format!("min_position {min} is greater than max_position {max}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查设定值，`last` 为上一次同类设定值及其距今时间
+    pub fn check_setpoint(&self, target: SetpointTarget, value: f32, last: Option<(f32, Duration)>) -> Result<(), SafetyViolation> {
+        let reject = |kind, value, limit| Err(SafetyViolation::new(kind, value, limit, SafetyAction::Rejected));
+        // NaN 与任何限制比较都不成立，必须先于限制检查拒绝
+        if !value.is_finite() {
+            return reject(SafetyKind::NotFinite, value, 0.0);
+        }
+        match target {
+            SetpointTarget::Speed => {
+                if let Some(max) = self.max_speed.filter(|max| value.abs() > *max) {
+                    return reject(SafetyKind::Speed, value, max);
+                }
+            }
+            SetpointTarget::Position => {
+                if let Some(min) = self.min_position.filter(|min| value < *min) {
+                    return reject(SafetyKind::Position, value, min);
+                }
+                if let Some(max) = self.max_position.filter(|max| value > *max) {
+                    return reject(SafetyKind::Position, value, max);
+                }
+            }
+        }
+        if let (Some(max_rate), Some((last, elapsed))) = (self.max_slew_rate, last) {
+            let dt = elapsed.min(MAX_SLEW_WINDOW).as_secs_f32().max(f32::EPSILON);
+            let rate = (value - last).abs() / dt;
+            if rate > max_rate {
+                return reject(SafetyKind::SlewRate, rate, max_rate);
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查反馈值，超限时返回需要停机的原因
+    pub fn check_feedback(&self, value: &FeedbackValue) -> Option<SafetyViolation> {
+        let stop = |kind, value, limit| Some(SafetyViolation::new(kind, value, limit, SafetyAction::Stopped));
+        match *value {
+            FeedbackValue::Speed(speed) => match self.max_speed {
+                Some(max) if speed.abs() > max => stop(SafetyKind::Speed, speed, max),
+                _ => None,
+            },
+            FeedbackValue::Position(position) => match (self.min_position, self.max_position) {
+                (Some(min), _) if position < min => stop(SafetyKind::Position, position, min),
+                (_, Some(max)) if position > max => stop(SafetyKind::Position, position, max),
+                _ => None,
+            },
+            FeedbackValue::Current(a, b, c) => {
+                let peak = a.abs().max(b.abs()).max(c.abs());
+                match self.max_current {
+                    Some(max) if peak > max => stop(SafetyKind::Current, peak, max),
+                    _ => None,
+                }
+            }
+            FeedbackValue::Udc(_) => None,
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use crate::command::{MotorConfigCommand, MotorRunCommand};
+use crate::error::MotorError;
+use crate::feedback_parser::FeedbackValue;
+use crate::invokes::AppState;
+use crate::motor::{Motor, MotorFeedbackState};
+use log::{error, warn};
+use rhai::{Dynamic, Engine, EvalAltResult};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::runtime::Handle;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{timeout, Duration};
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// 脚本运行控制，同一时间只允许运行一个脚本
+#[derive(Debug, Default)]
+pub struct ScriptControl {
+    running: AtomicBool,
+    abort: AtomicBool,
+}
+
+impl ScriptControl {
+    pub fn abort(&self) {
+        self.abort.store(true, Ordering::Relaxed);
+    }
+
+    fn is_aborted(&self) -> bool {
+        self.abort.load(Ordering::Relaxed)
+    }
+
+    /// 等到脚本被中止
+    async fn aborted(&self) {
+        while !self.is_aborted() {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptAssertion {
+    pub message: String,
+    pub passed: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScriptReport {
+    /// 所有断言通过且脚本正常结束
+    pub passed: bool,
+    pub assertions: Vec<ScriptAssertion>,
+    pub logs: Vec<String>,
+    /// 脚本运行错误（语法错误、运行时错误、被中止等）
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+struct ScriptContext {
+    app: AppHandle,
+    runtime: Handle,
+    control: Arc<ScriptControl>,
+    report: Mutex<ScriptReport>,
+}
+
+impl ScriptContext {
+    fn log(&self, message: String) {
+        if let Err(e) = self.app.emit("script-log", &message) {
+            error!("Tauri emit error {e}");
+        }
+        self.report.lock().unwrap().logs.push(message);
+    }
+
+    fn assert(&self, passed: bool, message: String) {
+        self.log(format!("[{}] {}", if passed { "PASS" } else { "FAIL" }, message));
+        self.report.lock().unwrap().assertions.push(ScriptAssertion { message, passed });
+    }
+
+    fn block_on<T>(&self, fut: impl Future<Output = Result<T, String>>) -> ScriptResult<T> {
+        self.runtime.block_on(fut).map_err(|e| e.into())
+    }
+
+    /// 获取当前连接的电机并执行操作，不长时间占用全局锁
+    fn with_motor<T, F, Fut>(&self, f: F) -> ScriptResult<T>
+    where
+        F: FnOnce(Arc<Motor>) -> Fut,
+        Fut: Future<Output = Result<T, MotorError>>,
+    {
+        self.block_on(async {
+            let motor = self.app.state::<AppState>().current_motor().await
+                .map_err(|e| e.to_string())?;
+            f(motor).await.map_err(|e| e.to_string())
+        })
+    }
+
+    /// 同 `with_motor`，但脚本中止时立即返回，用于校准等长时间操作
+    fn with_motor_abortable<T, F, Fut>(&self, f: F) -> ScriptResult<T>
+    where
+        F: FnOnce(Arc<Motor>) -> Fut,
+        Fut: Future<Output = Result<T, MotorError>>,
+    {
+        self.block_on(async {
+            let motor = self.app.state::<AppState>().current_motor().await
+                .map_err(|e| e.to_string())?;
+            tokio::select! {
+                result = f(motor) => result.map_err(|e| e.to_string()),
+                _ = self.control.aborted() => Err("script aborted".to_string()),
+            }
+        })
+    }
+
+    /// 可中止的等待
+    fn sleep(&self, ms: u64) -> ScriptResult<()> {
+        let deadline = Instant::now() + Duration::from_millis(ms);
+        while Instant::now() < deadline {
+            if self.control.is_aborted() {
+                return Err("script aborted".into());
+            }
+            let remain = deadline.saturating_duration_since(Instant::now());
+            std::thread::sleep(remain.min(Duration::from_millis(50)));
+        }
+        Ok(())
+    }
+
+    /// 等待下一个满足条件的反馈值
+    fn wait_feedback(&self, kind: MotorFeedbackState, timeout_ms: u64, pred: impl Fn(f32) -> bool) -> ScriptResult<Option<f32>> {
+        self.with_motor_abortable(|motor| async move {
+            let mut rx = motor.feedback_tx.subscribe();
+            motor.set_feedback(kind).await?;
+            let result = timeout(Duration::from_millis(timeout_ms), async {
+                loop {
+                    match rx.recv().await {
+                        Ok(sample) => {
+                            let value = match (kind, sample.value) {
+                                (MotorFeedbackState::Speed, FeedbackValue::Speed(v))
+                                | (MotorFeedbackState::Position, FeedbackValue::Position(v))
+                                | (MotorFeedbackState::Udc, FeedbackValue::Udc(v)) => v,
+                                (MotorFeedbackState::Current, FeedbackValue::Current(a, b, c)) => a.abs().max(b.abs()).max(c.abs()),
+                                _ => continue,
+                            };
+                            if pred(value) {
+                                return Ok(value);
+                            }
+                        }
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => return Err(MotorError::Disconnected),
+                    }
+                }
+            }).await;
+            match result {
+                Ok(value) => value.map(Some),
+                Err(_) => Ok(None),
+            }
+        })
+    }
+}
+
+fn num(v: &Dynamic) -> ScriptResult<f32> {
+    if let Ok(f) = v.as_float() {
+        Ok(f as f32)
+    } else if let Ok(i) = v.as_int() {
+        Ok(i as f32)
+    } else {
+        Err(format!("expected number, got {}", v.type_name()).into())
+    }
+}
+
+fn feedback_kind(kind: &str) -> ScriptResult<MotorFeedbackState> {
+    match kind.to_ascii_lowercase().as_str() {
+        "none" => Ok(MotorFeedbackState::None),
+        "speed" => Ok(MotorFeedbackState::Speed),
+        "position" => Ok(MotorFeedbackState::Position),
+        "current" | "iabc" => Ok(MotorFeedbackState::Current),
+        "udc" => Ok(MotorFeedbackState::Udc),
+        _ => Err(format!("unknown feedback kind: {kind}").into()),
+    }
+}
+
+fn compare(op: &str) -> ScriptResult<fn(f32, f32) -> bool> {
+    match op {
+        ">" => Ok(|a, b| a > b),
+        ">=" => Ok(|a, b| a >= b),
+        "<" => Ok(|a, b| a < b),
+        "<=" => Ok(|a, b| a <= b),
+        _ => Err(format!("unknown comparison: {op}").into()),
+    }
+}
+
+fn build_engine(ctx: &Arc<ScriptContext>) -> Engine {
+    let mut engine = Engine::new();
+
+    let c = Arc::clone(ctx);
+    engine.on_progress(move |_| c.control.is_aborted().then(|| Dynamic::from("script aborted")));
+    let c = Arc::clone(ctx);
+    engine.on_print(move |s| c.log(s.to_string()));
+    let c = Arc::clone(ctx);
+    engine.register_fn("log", move |s: &str| c.log(s.to_string()));
+    let c = Arc::clone(ctx);
+    engine.register_fn("assert", move |passed: bool, message: &str| c.assert(passed, message.to_string()));
+    let c = Arc::clone(ctx);
+    engine.register_fn("wait", move |ms: i64| c.sleep(ms.max(0) as u64));
+
+    // 连接
+    let c = Arc::clone(ctx);
+    engine.register_fn("connect", move |port: &str, baud_rate: i64| {
+        let port = port.to_string();
+        c.block_on(async { c.app.state::<AppState>().connect(port, baud_rate as u32).await.map_err(|e| e.to_string()) })
+    });
+    let c = Arc::clone(ctx);
+    engine.register_fn("disconnect", move || c.block_on(async { c.app.state::<AppState>().disconnect().await.map_err(|e| e.to_string()) }));
+    let c = Arc::clone(ctx);
+    engine.register_fn("state", move || c.with_motor(|m| async move { Ok(m.state().to_string()) }));
+
+    // 运行
+    let c = Arc::clone(ctx);
+    engine.register_fn("set_speed", move |v: Dynamic| {
+        let v = num(&v)?;
+        c.with_motor(|m| async move { m.send_running_command(&MotorRunCommand::SetSpeed(v)).await })
+    });
+    let c = Arc::clone(ctx);
+    engine.register_fn("set_position", move |v: Dynamic| {
+        let v = num(&v)?;
+        c.with_motor(|m| async move { m.send_running_command(&MotorRunCommand::SetPosition(v)).await })
+    });
+    let c = Arc::clone(ctx);
+    engine.register_fn("stop", move || c.with_motor(|m| async move { m.send_running_command(&MotorRunCommand::Stop).await }));
+    let c = Arc::clone(ctx);
+    engine.register_fn("calibrate", move || c.with_motor_abortable(|m| async move { m.calibration().await }));
+
+    // 配置
+    let c = Arc::clone(ctx);
+    engine.register_fn("load_config", move || {
+        let config = c.with_motor(|m| async move { m.load_config().await })?;
+        rhai::serde::to_dynamic(config)
+    });
+    let c = Arc::clone(ctx);
+    engine.register_fn("save_config", move || c.with_motor(|m| async move { m.save_config().await }));
+    let c = Arc::clone(ctx);
+    engine.register_fn("config_speed_pi", move |kp: Dynamic, ki: Dynamic, output_max: Dynamic| {
+        let cmd = MotorConfigCommand::ConfigSpeedPi { kp: num(&kp)?, ki: num(&ki)?, output_max: num(&output_max)? };
+        c.with_motor(|m| async move { m.send_config_command(&cmd).await })
+    });
+    let c = Arc::clone(ctx);
+    engine.register_fn("config_position_pid", move |kp: Dynamic, ki: Dynamic, kd: Dynamic, output_max: Dynamic| {
+        let cmd = MotorConfigCommand::ConfigPositionPid { kp: num(&kp)?, ki: num(&ki)?, kd: num(&kd)?, output_max: num(&output_max)? };
+        c.with_motor(|m| async move { m.send_config_command(&cmd).await })
+    });
+    let c = Arc::clone(ctx);
+    engine.register_fn("config_current_pi", move |id_kp: Dynamic, id_ki: Dynamic, iq_kp: Dynamic, iq_ki: Dynamic| {
+        let cmd = MotorConfigCommand::ConfigCurrentPi { id_kp: num(&id_kp)?, id_ki: num(&id_ki)?, iq_kp: num(&iq_kp)?, iq_ki: num(&iq_ki)? };
+        c.with_motor(|m| async move { m.send_config_command(&cmd).await })
+    });
+    let c = Arc::clone(ctx);
+    engine.register_fn("config_id", move |id: i64| {
+        let id = u8::try_from(id).map_err(|_| format!("invalid id: {id}"))?;
+        c.with_motor(|m| async move { m.send_config_command(&MotorConfigCommand::ConfigId(id)).await })
+    });
+    let c = Arc::clone(ctx);
+    engine.register_fn("config_udc", move |udc: Dynamic| {
+        let cmd = MotorConfigCommand::ConfigUdc(num(&udc)?);
+        c.with_motor(|m| async move { m.send_config_command(&cmd).await })
+    });
+    let c = Arc::clone(ctx);
+    engine.register_fn("config_idq_filter", move |fc: Dynamic| {
+        let cmd = MotorConfigCommand::ConfigIdqFilter(num(&fc)?);
+        c.with_motor(|m| async move { m.send_config_command(&cmd).await })
+    });
+
+    // 反馈
+    let c = Arc::clone(ctx);
+    engine.register_fn("set_feedback", move |kind: &str| {
+        let kind = feedback_kind(kind)?;
+        c.with_motor(|m| async move { m.set_feedback(kind).await })
+    });
+    let c = Arc::clone(ctx);
+    engine.register_fn("read_feedback", move |kind: &str, timeout_ms: i64| -> ScriptResult<Dynamic> {
+        let kind = feedback_kind(kind)?;
+        let value = c.wait_feedback(kind, timeout_ms.max(0) as u64, |_| true)?;
+        Ok(value.map(|v| Dynamic::from_float(v as f64)).unwrap_or(Dynamic::UNIT))
+    });
+    let c = Arc::clone(ctx);
+    engine.register_fn("wait_feedback", move |kind: &str, op: &str, threshold: Dynamic, timeout_ms: i64| -> ScriptResult<bool> {
+        let kind = feedback_kind(kind)?;
+        let op = compare(op)?;
+        let threshold = num(&threshold)?;
+        let value = c.wait_feedback(kind, timeout_ms.max(0) as u64, move |v| op(v, threshold))?;
+        Ok(value.is_some())
+    });
+
+    engine
+}
+
+/// 在阻塞线程中运行脚本并返回测试报告
+pub async fn run_script(app: AppHandle, control: Arc<ScriptControl>, source: String) -> Result<ScriptReport, MotorError> {
+    if control.running.swap(true, Ordering::AcqRel) {
+        return Err(MotorError::Busy("a script is already running".into()));
+    }
+    control.abort.store(false, Ordering::Relaxed);
+
+    let ctx = Arc::new(ScriptContext {
+        app,
+        runtime: Handle::current(),
+        control: Arc::clone(&control),
+        report: Mutex::new(ScriptReport::default()),
+    });
+    let start = Instant::now();
+    let task_ctx = Arc::clone(&ctx);
+    let result = tokio::task::spawn_blocking(move || build_engine(&task_ctx).run(&source)).await;
+
+    let mut report = ctx.report.lock().unwrap().clone();
+    report.error = match result {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(e) => Some(e.to_string()),
+    };
+    report.passed = report.error.is_none() && report.assertions.iter().all(|a| a.passed);
+    // 脚本出错、被中止或断言失败时电机可能仍在运行，统一停机
+    if !report.passed {
+        if let Ok(motor) = ctx.app.state::<AppState>().current_motor().await {
+            if let Err(e) = motor.send_running_command(&MotorRunCommand::Stop).await {
+                warn!("Failed to stop motor after script: {e}");
+            }
+        }
+    }
+    control.running.store(false, Ordering::Release);
+    report.duration_ms = start.elapsed().as_millis() as u64;
+    Ok(report)
+}
@@ -1,28 +1,135 @@
 use crate::exit_signal::ExitSignal;
-use log::{debug, error};
+use crate::feedback_parser::FeedbackValue;
+use crate::frame::{Message, Protocol, MAX_FRAME_SIZE, PROTOCOL_BINARY_ACK, PROTOCOL_TEXT_ACK};
+use crate::line_framer::{Framed, LineFramer, MAX_LINE_LENGTH};
+use crate::link_stats::LinkStats;
+use log::{debug, error, warn};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fmt::Debug;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use std::sync::{Arc, Weak};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf};
 use tokio::sync::{broadcast, Mutex};
 use tokio::task::JoinHandle;
-use tokio_serial::{SerialPortBuilderExt, SerialStream};
+use tokio::time::{timeout, Duration};
+use tokio_serial::{SerialPortBuilderExt, SerialPortInfo, SerialPortType};
+
+/// 电调 USB 描述符中的产品名前缀，不区分大小写
+const ESC_PRODUCT_PREFIX: &str = "IPM ESC";
+
+/// 电调 USB 序列号前缀
+const ESC_SERIAL_PREFIX: &str = "IPMESC";
+
+/// 常见的 USB 转串口芯片与 STM32 虚拟串口 (VID, PID)，其他设备同样使用，只作为提示
+const USB_UART_IDS: &[(u16, u16)] = &[
+    (0x0483, 0x5740), // STM32 VCP
+    (0x1a86, 0x7523), // CH340
+    (0x1a86, 0x55d3), // CH343
+    (0x10c4, 0xea60), // CP210x
+    (0x0403, 0x6001), // FT232R
+];
+
+/// 急停写入的最长时间，超时说明写入卡死，直接放弃该设备
+const EMERGENCY_WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// 接收广播的容量，1kHz 反馈下可缓冲约 8 秒，订阅者偶尔卡顿不会丢消息
+pub const RECV_CHANNEL_CAPACITY: usize = 8192;
+
+/// 所有打开过的串口，急停时绕过上层的锁直接写入
+static OPEN_DEVICES: std::sync::Mutex<Vec<Weak<SerialDevice>>> = std::sync::Mutex::new(Vec::new());
+
+/// 串口的读写流，测试时可用内存中的双工流代替
+pub trait PortStream: AsyncRead + AsyncWrite + Send + Unpin + Debug {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin + Debug> PortStream for T {}
+
+/// 串口描述信息，用于前端列表展示与自动选择
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SerialPortDescriptor {
+    pub port_name: String,
+    /// usb / pci / bluetooth / unknown
+    pub port_type: String,
+    pub vid: Option<u16>,
+    pub pid: Option<u16>,
+    pub serial_number: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    /// USB 产品名或序列号表明是我们的电调
+    pub is_esc: bool,
+    /// 常见的 USB 转串口芯片，可能是电调，需要探测确认
+    pub usb_uart: bool,
+}
+
+impl SerialPortDescriptor {
+    pub fn list() -> tokio_serial::Result<Vec<Self>> {
+        Ok(tokio_serial::available_ports()?.iter().map(Self::from).collect())
+    }
+
+    /// 只根据电调自己的产品名或序列号判断，通用串口芯片无法区分
+    fn looks_like_esc(product: Option<&str>, serial_number: Option<&str>) -> bool {
+        let has_prefix = |s: &str, prefix: &str| s.get(..prefix.len()).is_some_and(|p| p.eq_ignore_ascii_case(prefix));
+        product.is_some_and(|p| has_prefix(p.trim(), ESC_PRODUCT_PREFIX))
+            || serial_number.is_some_and(|s| has_prefix(s.trim(), ESC_SERIAL_PREFIX))
+    }
+}
+
+impl From<&SerialPortInfo> for SerialPortDescriptor {
+    fn from(info: &SerialPortInfo) -> Self {
+        let mut desc = Self {
+            port_name: info.port_name.clone(),
+            port_type: "unknown".into(),
+            vid: None,
+            pid: None,
+            serial_number: None,
+            manufacturer: None,
+            product: None,
+            is_esc: false,
+            usb_uart: false,
+        };
+        match &info.port_type {
+            SerialPortType::UsbPort(usb) => {
+                desc.port_type = "usb".into();
+                desc.vid = Some(usb.vid);
+                desc.pid = Some(usb.pid);
+                desc.serial_number = usb.serial_number.clone();
+                desc.manufacturer = usb.manufacturer.clone();
+                desc.product = usb.product.clone();
+                desc.is_esc = Self::looks_like_esc(usb.product.as_deref(), usb.serial_number.as_deref());
+                desc.usb_uart = USB_UART_IDS.contains(&(usb.vid, usb.pid));
+            }
+            SerialPortType::PciPort => desc.port_type = "pci".into(),
+            SerialPortType::BluetoothPort => desc.port_type = "bluetooth".into(),
+            SerialPortType::Unknown => {}
+        }
+        desc
+    }
+}
 
 #[derive(Debug)]
 pub struct SerialDevice {
     pub port_name: String,
     pub baud_rate: u32,
-    pub reader: Mutex<Option<tokio::io::ReadHalf<SerialStream>>>,
-    pub writer: Mutex<Option<tokio::io::WriteHalf<SerialStream>>>,
+    pub reader: Mutex<Option<ReadHalf<Box<dyn PortStream>>>>,
+    pub writer: Mutex<Option<WriteHalf<Box<dyn PortStream>>>>,
     pub connected: AtomicBool,
+    /// 急停锁存，置位后只允许发送 stop，需要手动解除
+    pub emergency_stopped: AtomicBool,
+    /// 使用二进制帧协议，收到协议切换的应答时由读取任务切换，收发同时生效
+    pub binary: AtomicBool,
     /// 串口接收事件广播
     pub recv_event_tx: broadcast::Sender<String>,
+    /// 二进制协议下的反馈数据，不再经过文本解析
+    pub recv_feedback_tx: broadcast::Sender<FeedbackValue>,
+    pub stats: LinkStats,
     recv_loop_handle: Mutex<Option<JoinHandle<()>>>,
     recv_loop_exit_signal: Arc<ExitSignal>,
 }
 
 impl SerialDevice {
     pub fn new(port_name: String, baud_rate: u32) -> Arc<Self> {
-        let (recv_event_tx, _) = broadcast::channel(1024);
+        let (recv_event_tx, _) = broadcast::channel(RECV_CHANNEL_CAPACITY);
+        let (recv_feedback_tx, _) = broadcast::channel(RECV_CHANNEL_CAPACITY);
 
         Arc::new(Self {
             port_name,
@@ -30,7 +137,11 @@ impl SerialDevice {
             reader: Mutex::new(None),
             writer: Mutex::new(None),
             connected: AtomicBool::new(false),
+            emergency_stopped: AtomicBool::new(false),
+            binary: AtomicBool::new(false),
             recv_event_tx,
+            recv_feedback_tx,
+            stats: LinkStats::default(),
             recv_loop_handle: Mutex::new(None),
             recv_loop_exit_signal: ExitSignal::new(),
         })
@@ -39,6 +150,12 @@ impl SerialDevice {
     pub async fn connect(self: &Arc<Self>) -> Result<(), String> {
         let stream = tokio_serial::new(&self.port_name, self.baud_rate)
             .open_native_async().map_err(|e| e.to_string())?;
+        self.attach(Box::new(stream)).await;
+        Ok(())
+    }
+
+    /// 使用已打开的流作为串口并启动读取任务
+    pub async fn attach(self: &Arc<Self>, stream: Box<dyn PortStream>) {
         let (reader, writer) = tokio::io::split(stream);
         *self.reader.lock().await = Some(reader);
         *self.writer.lock().await = Some(writer);
@@ -49,12 +166,17 @@ impl SerialDevice {
             this.read_loop().await;
         });
         *self.recv_loop_handle.lock().await = Some(handle);
-        Ok(())
+        {
+            let mut devices = OPEN_DEVICES.lock().unwrap();
+            devices.retain(|d| d.strong_count() > 0);
+            devices.push(Arc::downgrade(self));
+        }
     }
 
     async fn read_loop(self: Arc<Self>) {
         let mut buf = vec![0u8; 1024];
-        let mut cache = String::new();
+        let mut cache: Vec<u8> = Vec::new();
+        let mut framer = LineFramer::new(MAX_LINE_LENGTH);
 
         loop {
             let mut guard = self.reader.lock().await;
@@ -76,17 +198,9 @@ impl SerialDevice {
                             return;
                         }
                     };
-                    cache.push_str(&String::from_utf8_lossy(&buf[..n]));
-                    // 分行（包含 get_speed、get_current、get_config 等所有返回）
-                    while let Some(pos) = cache.find("\r\n") {
-                        let line = cache[..pos].trim().to_string();
-                        cache.drain(..pos + 2);
-
-                        if !line.is_empty() {
-                            // 广播给所有订阅者
-                            let _ = self.recv_event_tx.send(line);
-                        }
-                    }
+                    self.stats.bytes_in.fetch_add(n as u64, Ordering::Relaxed);
+                    cache.extend_from_slice(&buf[..n]);
+                    self.split_received(&mut cache, &mut framer);
                 }
                 _ = self.recv_loop_exit_signal.wait() => {
                     return;
@@ -95,14 +209,135 @@ impl SerialDevice {
         }
     }
 
+    /// 从接收缓存中取出完整的行或帧，协议可能在中途切换，每次都重新判断
+    fn split_received(&self, cache: &mut Vec<u8>, framer: &mut LineFramer) {
+        loop {
+            if self.binary.load(Ordering::Relaxed) {
+                // 协议切换应答的换行可能还剩下 `\n`
+                let skip = framer.pending_lf(cache);
+                cache.drain(..skip);
+                let Some(pos) = cache.iter().position(|b| *b == 0) else {
+                    // 超长仍没有分隔符，说明数据已错乱，丢弃等待下一帧
+                    if cache.len() > MAX_FRAME_SIZE * 2 {
+                        warn!("Dropped {} bytes without frame delimiter", cache.len());
+                        cache.clear();
+                    }
+                    return;
+                };
+                let frame: Vec<u8> = cache.drain(..=pos).collect();
+                if pos == 0 {
+                    continue;
+                }
+                match Message::decode(&frame[..pos]) {
+                    Ok(Message::Text(line)) => self.dispatch_line(line),
+                    Ok(Message::Feedback(value)) => {
+                        let _ = self.recv_feedback_tx.send(value);
+                    }
+                    Ok(message) => debug!("Unexpected message from device: {message:?}"),
+                    // 损坏的帧直接丢弃，不会被当作有效数据
+                    Err(e) => {
+                        self.stats.framing.corrupted_frames.fetch_add(1, Ordering::Relaxed);
+                        warn!("Dropped corrupted frame: {e}");
+                    }
+                }
+            } else {
+                // 分行（包含 get_speed、get_current、get_config 等所有返回），每次取一行以便中途切换协议
+                let (consumed, framed) = framer.feed(cache, &self.stats.framing);
+                cache.drain(..consumed);
+                match framed {
+                    Some(Framed::Line(line)) => self.dispatch_line(line.trim().to_string()),
+                    Some(Framed::Overflow(dropped)) => warn!("Dropped {dropped} bytes of overlong line from {}", self.port_name),
+                    None => return,
+                }
+            }
+        }
+    }
+
+    fn dispatch_line(&self, line: String) {
+        if line.is_empty() {
+            return;
+        }
+        self.stats.lines_received.fetch_add(1, Ordering::Relaxed);
+        if line == PROTOCOL_BINARY_ACK {
+            self.binary.store(true, Ordering::Relaxed);
+        } else if line == PROTOCOL_TEXT_ACK {
+            self.binary.store(false, Ordering::Relaxed);
+        }
+        // 广播给所有订阅者
+        let _ = self.recv_event_tx.send(line);
+    }
+
+    /// 按当前协议编码文本指令并写入
+    async fn write_command(&self, writer: &mut WriteHalf<Box<dyn PortStream>>, text: &str) -> Result<(), String> {
+        let bytes = if self.binary.load(Ordering::Relaxed) {
+            Cow::Owned(Message::from_command(text).encode())
+        } else {
+            Cow::Borrowed(text.as_bytes())
+        };
+        writer.write_all(&bytes).await.map_err(|e| e.to_string())?;
+        self.stats.bytes_out.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+        self.stats.commands_sent.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// 当前处于连接状态的所有串口
+    pub fn open_devices() -> Vec<Arc<Self>> {
+        OPEN_DEVICES.lock().unwrap().iter()
+            .filter_map(Weak::upgrade)
+            .filter(|d| d.connected.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    /// 急停：置位锁存并直接写入，不经过 Motor 的任何锁
+    pub async fn emergency_stop(&self, line: &str) -> Result<(), String> {
+        debug!("serial emergency stop: {line}");
+        let result = timeout(EMERGENCY_WRITE_TIMEOUT, async {
+            let mut writer = self.writer.lock().await;
+            // 在写锁内置位，与 send_unless_stopped 互斥，之后不会再写入运行指令
+            self.emergency_stopped.store(true, Ordering::Relaxed);
+            match writer.as_mut() {
+                Some(writer) => self.write_command(writer, line).await,
+                None => Err("serial not connected".to_string()),
+            }
+        }).await;
+        // 写锁超时同样需要锁存
+        self.emergency_stopped.store(true, Ordering::Relaxed);
+        result.unwrap_or_else(|_| Err("serial write timed out".into()))
+    }
+
+    /// 发送运行指令，在写锁内检查急停锁存，已急停时不写入并返回 false
+    pub async fn send_unless_stopped(&self, text: &str) -> Result<bool, String> {
+        let mut writer = self.writer.lock().await;
+        if self.emergency_stopped.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+        debug!("serial send: {text}");
+        match writer.as_mut() {
+            Some(writer) => self.write_command(writer, text).await.map(|_| true),
+            None => Err("serial not connected".into()),
+        }
+    }
+
+    /// 下位机可能停留在上次会话的二进制协议，连接后先发送二进制的 `protocol text` 帧并以换行结束：
+    /// 二进制协议下切换回文本，文本协议下只是一行无效指令，应答帧会被分行时丢弃
+    pub async fn reset_remote_protocol(&self) -> Result<(), String> {
+        let mut bytes = Message::Text(format!("protocol {}", Protocol::Text)).encode();
+        bytes.extend_from_slice(b"\r\n");
+        let mut writer = self.writer.lock().await;
+        let Some(writer) = writer.as_mut() else {
+            return Err("serial not connected".into());
+        };
+        writer.write_all(&bytes).await.map_err(|e| e.to_string())?;
+        self.stats.bytes_out.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+        Ok(())
+    }
+
     pub async fn send(&self, text: &str) -> Result<(), String> {
         let mut writer = self.writer.lock().await;
         debug!("serial send: {text}");
 
         if let Some(ref mut writer) = *writer {
-            writer.write_all(text.as_bytes())
-                .await
-                .map_err(|e| e.to_string())
+            self.write_command(writer, text).await
         } else {
             Err("serial not connected".into())
         }
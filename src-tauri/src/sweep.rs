@@ -0,0 +1,311 @@
+use crate::command::{MotorConfigCommand, MotorRunCommand, MotorState};
+use crate::error::MotorError;
+use crate::events::MotorEvent;
+use crate::feedback_parser::FeedbackValue;
+use crate::motor::{Motor, MotorFeedbackState, Timestamped};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::Arc;
+use tokio::select;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{sleep, timeout, Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SweepLoop {
+    Speed,
+    Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SweepSortKey {
+    Overshoot,
+    RiseTime,
+    SettlingTime,
+    Iae,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GainSet {
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+}
+
+/// 参数网格，各参数取值做笛卡尔积
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepGrid {
+    pub kp: Vec<f32>,
+    pub ki: Vec<f32>,
+    /// 仅位置环使用，为空时视为 0
+    #[serde(default)]
+    pub kd: Vec<f32>,
+}
+
+impl SweepGrid {
+    pub fn gain_sets(&self, target: SweepLoop) -> Vec<GainSet> {
+        let kd = match target {
+            SweepLoop::Position if !self.kd.is_empty() => self.kd.clone(),
+            _ => vec![0f32],
+        };
+        let mut sets = Vec::with_capacity(self.kp.len() * self.ki.len() * kd.len());
+        for &kp in &self.kp {
+            for &ki in &self.ki {
+                for &kd in &kd {
+                    sets.push(GainSet { kp, ki, kd });
+                }
+            }
+        }
+        sets
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepRequest {
+    pub target: SweepLoop,
+    pub grid: SweepGrid,
+    /// 输出限幅，为空时沿用当前配置
+    pub output_max: Option<f32>,
+    /// 阶跃幅值，速度环为目标速度，位置环为相对起始位置的位移
+    pub step: f32,
+    /// 每组参数的记录时长
+    pub duration_ms: u64,
+    /// 每组参数测试后停机等待的时长
+    pub rest_ms: u64,
+    /// 调节时间的误差带，相对阶跃幅值的比例（如 0.02）
+    pub settle_band: f32,
+    pub sort_by: Option<SweepSortKey>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepMetrics {
+    /// 超调量，百分比
+    pub overshoot_percent: f32,
+    /// 10% 到 90% 的上升时间
+    pub rise_time_ms: Option<f32>,
+    /// 进入并保持在误差带内的时间
+    pub settling_time_ms: Option<f32>,
+    /// 记录窗口最后 20% 的平均绝对误差
+    pub steady_state_error: f32,
+    /// 误差绝对值积分
+    pub iae: f32,
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepResult {
+    pub gains: GainSet,
+    pub metrics: StepMetrics,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepProgress {
+    pub index: usize,
+    pub total: usize,
+    pub gains: GainSet,
+}
+
+/// 原始配置，用于测试结束后恢复
+enum OriginalGains {
+    Speed { kp: f32, ki: f32, output_max: f32 },
+    Position { kp: f32, ki: f32, kd: f32, output_max: f32 },
+}
+
+impl OriginalGains {
+    fn into_command(self) -> MotorConfigCommand {
+        match self {
+            OriginalGains::Speed { kp, ki, output_max } => MotorConfigCommand::ConfigSpeedPi { kp, ki, output_max },
+            OriginalGains::Position { kp, ki, kd, output_max } => MotorConfigCommand::ConfigPositionPid { kp, ki, kd, output_max },
+        }
+    }
+}
+
+/// 依次应用每组参数并进行阶跃测试，结束后（无论成功与否）恢复原始参数
+pub async fn run_gain_sweep(motor: &Arc<Motor>, request: &SweepRequest) -> Result<Vec<SweepResult>, MotorError> {
+    // 缓存可能已过时（如原始指令修改过参数），每次都从下位机读取，读取失败则不开始
+    let config = motor.load_config().await?;
+    let original = match request.target {
+        SweepLoop::Speed => OriginalGains::Speed {
+            kp: config.speed_pi.kp,
+            ki: config.speed_pi.ki,
+            output_max: config.speed_pi.output_max,
+        },
+        SweepLoop::Position => OriginalGains::Position {
+            kp: config.position_pid.kp,
+            ki: config.position_pid.ki,
+            kd: config.position_pid.kd,
+            output_max: config.position_pid.output_max,
+        },
+    };
+    let output_max = request.output_max.unwrap_or(match original {
+        OriginalGains::Speed { output_max, .. } | OriginalGains::Position { output_max, .. } => output_max,
+    });
+    let unsaved = motor.unsaved.load(Relaxed);
+
+    let result = sweep(motor, request, output_max).await;
+
+    // 恢复原始参数，停机失败也要尝试恢复；恢复成功后下位机参数与测试前一致
+    let stopped = motor.send_running_command(&MotorRunCommand::Stop).await;
+    let restored = motor.send_config_command(&original.into_command()).await;
+    if restored.is_ok() {
+        motor.unsaved.store(unsaved, Relaxed);
+    }
+    // 测试本身失败（如故障）时恢复往往也会失败，优先返回测试的错误
+    if result.is_err() {
+        if let Err(e) = &stopped {
+            warn!("Failed to stop motor after gain sweep: {e}");
+        }
+        if let Err(e) = &restored {
+            warn!("Failed to restore gains after gain sweep: {e}");
+        }
+    } else {
+        stopped?;
+        restored?;
+    }
+
+    let mut results = result?;
+    if let Some(key) = request.sort_by {
+        sort_results(&mut results, key);
+    }
+    Ok(results)
+}
+
+async fn sweep(motor: &Arc<Motor>, request: &SweepRequest, output_max: f32) -> Result<Vec<SweepResult>, MotorError> {
+    let sets = request.grid.gain_sets(request.target);
+    let mut results = Vec::with_capacity(sets.len());
+    for (index, gains) in sets.iter().enumerate() {
+        motor.events.emit(MotorEvent::SweepProgress(SweepProgress { index, total: sets.len(), gains: *gains }));
+        let cmd = match request.target {
+            SweepLoop::Speed => MotorConfigCommand::ConfigSpeedPi { kp: gains.kp, ki: gains.ki, output_max },
+            SweepLoop::Position => MotorConfigCommand::ConfigPositionPid { kp: gains.kp, ki: gains.ki, kd: gains.kd, output_max },
+        };
+        motor.send_config_command(&cmd).await?;
+        let metrics = step_test(motor, request).await?;
+        results.push(SweepResult { gains: *gains, metrics });
+        sleep(Duration::from_millis(request.rest_ms)).await;
+    }
+    Ok(results)
+}
+
+/// 单次阶跃测试，返回后电机处于停止状态
+async fn step_test(motor: &Arc<Motor>, request: &SweepRequest) -> Result<StepMetrics, MotorError> {
+    let feedback = match request.target {
+        SweepLoop::Speed => MotorFeedbackState::Speed,
+        SweepLoop::Position => MotorFeedbackState::Position,
+    };
+    let mut rx = motor.feedback_tx.subscribe();
+    motor.set_feedback(feedback).await?;
+    // 读取起始值
+    let initial = timeout(Duration::from_secs(1), next_value(&mut rx, request.target))
+        .await
+        .map_err(|_| MotorError::Timeout)??;
+    let setpoint = match request.target {
+        SweepLoop::Speed => request.step,
+        SweepLoop::Position => initial + request.step,
+    };
+    let cmd = match request.target {
+        SweepLoop::Speed => MotorRunCommand::SetSpeed(setpoint),
+        SweepLoop::Position => MotorRunCommand::SetPosition(setpoint),
+    };
+
+    let start = Instant::now();
+    motor.send_running_command(&cmd).await?;
+    let mut state_rx = motor.subscribe_state();
+    let mut samples = vec![(0f32, initial)];
+    let window = Duration::from_millis(request.duration_ms);
+    // 与校准一样，stop、急停或故障改变状态后中止整个扫描
+    let _ = timeout(window, async {
+        select! {
+            _ = async {
+                while let Ok(value) = next_value(&mut rx, request.target).await {
+                    samples.push((start.elapsed().as_secs_f32() * 1000f32, value));
+                }
+            } => {}
+            _ = state_rx.wait_for(|state| *state != MotorState::DebugRun) => {}
+        }
+    }).await;
+    match motor.state() {
+        MotorState::DebugRun => {}
+        MotorState::Fault => return Err(motor.fault_detected().await),
+        state => return Err(MotorError::invalid_state(state, "continue gain sweep")),
+    }
+    motor.send_running_command(&MotorRunCommand::Stop).await?;
+
+    Ok(step_metrics(&samples, setpoint, request.settle_band))
+}
+
+async fn next_value(rx: &mut broadcast::Receiver<Timestamped<FeedbackValue>>, target: SweepLoop) -> Result<f32, MotorError> {
+    loop {
+        match rx.recv().await {
+            Ok(sample) => match (target, sample.value) {
+                (SweepLoop::Speed, FeedbackValue::Speed(v)) | (SweepLoop::Position, FeedbackValue::Position(v)) => return Ok(v),
+                _ => continue,
+            },
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return Err(MotorError::Disconnected),
+        }
+    }
+}
+
+/// 根据 (时间 ms, 值) 序列计算阶跃响应指标，第一个点为起始值
+pub fn step_metrics(samples: &[(f32, f32)], setpoint: f32, settle_band: f32) -> StepMetrics {
+    let initial = samples.first().map(|s| s.1).unwrap_or(0f32);
+    let step = setpoint - initial;
+    let direction = if step >= 0f32 { 1f32 } else { -1f32 };
+    let amplitude = step.abs().max(f32::EPSILON);
+
+    let overshoot = samples.iter()
+        .map(|&(_, v)| (v - setpoint) * direction)
+        .fold(0f32, f32::max);
+
+    let crossing = |fraction: f32| samples.iter()
+        .find(|&&(_, v)| (v - initial) * direction >= fraction * amplitude)
+        .map(|s| s.0);
+    let rise_time_ms = match (crossing(0.1), crossing(0.9)) {
+        (Some(t10), Some(t90)) => Some(t90 - t10),
+        _ => None,
+    };
+
+    // 最后一次超出误差带之后的下一个采样点即为调节时间
+    let band = settle_band * amplitude;
+    let settling_time_ms = match samples.iter().rposition(|&(_, v)| (v - setpoint).abs() > band) {
+        None => samples.first().map(|s| s.0),
+        Some(i) => samples.get(i + 1).map(|s| s.0),
+    };
+
+    let tail = &samples[samples.len() - samples.len() / 5..];
+    let steady_state_error = if tail.is_empty() {
+        0f32
+    } else {
+        tail.iter().map(|&(_, v)| (setpoint - v).abs()).sum::<f32>() / tail.len() as f32
+    };
+
+    let iae = samples.windows(2)
+        .map(|w| (w[1].0 - w[0].0) / 1000f32 * (setpoint - w[1].1).abs())
+        .sum();
+
+    StepMetrics {
+        overshoot_percent: overshoot / amplitude * 100f32,
+        rise_time_ms,
+        settling_time_ms,
+        steady_state_error,
+        iae,
+        samples: samples.len(),
+    }
+}
+
+/// 指标越小越好，无法计算的指标排在最后
+pub fn sort_results(results: &mut [SweepResult], key: SweepSortKey) {
+    let value = |r: &SweepResult| match key {
+        SweepSortKey::Overshoot => Some(r.metrics.overshoot_percent),
+        SweepSortKey::RiseTime => r.metrics.rise_time_ms,
+        SweepSortKey::SettlingTime => r.metrics.settling_time_ms,
+        SweepSortKey::Iae => Some(r.metrics.iae),
+    };
+    results.sort_by(|a, b| {
+        let a = value(a).unwrap_or(f32::INFINITY);
+        let b = value(b).unwrap_or(f32::INFINITY);
+        a.total_cmp(&b)
+    });
+}
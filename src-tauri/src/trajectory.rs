@@ -0,0 +1,158 @@
+use crate::error::MotorError;
+use crate::events::MotorEvent;
+use crate::feedback_parser::FeedbackValue;
+use crate::motor::Motor;
+use crate::waveform::SetpointTarget;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::select;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Instant;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TrajectoryPoint {
+    /// 秒
+    pub time: f32,
+    pub value: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Trajectory {
+    points: Vec<TrajectoryPoint>,
+}
+
+impl Trajectory {
+    pub fn new(mut points: Vec<TrajectoryPoint>) -> Result<Self, MotorError> {
+        if points.is_empty() {
+            return Err(MotorError::InvalidArgument("trajectory is empty".into()));
+        }
+        if points.windows(2).any(|w| w[1].time <= w[0].time) {
+            return Err(MotorError::InvalidArgument("trajectory time must be strictly increasing".into()));
+        }
+        // 从 0 时刻开始回放
+        let t0 = points[0].time;
+        for p in points.iter_mut() {
+            p.time -= t0;
+        }
+        Ok(Self { points })
+    }
+
+    /// 按扩展名加载，`.json` 为 `[{ "time": .., "value": .. }]`，其余按 `time,value` 的 CSV 解析
+    pub fn load(path: &Path) -> Result<Self, MotorError> {
+        let text = std::fs::read_to_string(path)?;
+        let is_json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if is_json {
+            let points: Vec<TrajectoryPoint> = serde_json::from_str(&text).map_err(|e| MotorError::ParseError(e.to_string()))?;
+            Self::new(points)
+        } else {
+            Self::from_csv(&text)
+        }
+    }
+
+    pub fn from_csv(text: &str) -> Result<Self, MotorError> {
+        let mut points = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split([',', ';', '\t']).map(str::trim);
+            let parsed = match (fields.next(), fields.next()) {
+                (Some(t), Some(v)) => t.parse::<f32>().ok().zip(v.parse::<f32>().ok()),
+                _ => None,
+            };
+            match parsed {
+                Some((time, value)) => points.push(TrajectoryPoint { time, value }),
+                // 允许首行为表头
+                None if points.is_empty() && i == 0 => continue,
+                None => return Err(MotorError::ParseError(format!("invalid trajectory line {}: {}", i + 1, line))),
+            }
+        }
+        Self::new(points)
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.points.last().map(|p| p.time).unwrap_or(0f32)
+    }
+
+    /// 线性插值，超出结束时间返回 None
+    pub fn value_at(&self, t: f32) -> Option<f32> {
+        if t > self.duration() {
+            return None;
+        }
+        let i = self.points.partition_point(|p| p.time <= t);
+        if i == 0 {
+            return Some(self.points[0].value);
+        }
+        let a = self.points[i - 1];
+        match self.points.get(i) {
+            Some(b) => Some(a.value + (b.value - a.value) * (t - a.time) / (b.time - a.time)),
+            None => Some(a.value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingSample {
+    pub time: f32,
+    pub reference: f32,
+    pub actual: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingReport {
+    /// 是否完整回放到结束
+    pub completed: bool,
+    pub rms_error: f32,
+    pub max_abs_error: f32,
+    pub mean_error: f32,
+    pub samples: Vec<TrackingSample>,
+}
+
+impl TrackingReport {
+    fn new(completed: bool, samples: Vec<TrackingSample>) -> Self {
+        let n = samples.len().max(1) as f32;
+        let errors = samples.iter().map(|s| s.actual - s.reference);
+        let rms_error = (errors.clone().map(|e| e * e).sum::<f32>() / n).sqrt();
+        let max_abs_error = errors.clone().map(f32::abs).fold(0f32, f32::max);
+        let mean_error = errors.sum::<f32>() / n;
+        Self { completed, rms_error, max_abs_error, mean_error, samples }
+    }
+}
+
+/// 开始回放轨迹，回放结束后通过 `trajectory-report` 事件返回跟踪误差
+pub async fn play_trajectory(motor: &Arc<Motor>, trajectory: Trajectory, target: SetpointTarget, rate_hz: f32) -> Result<(), MotorError> {
+    let mut rx = motor.feedback_tx.subscribe();
+    let reference = trajectory.clone();
+    let start = Instant::now();
+    let finished = motor.start_setpoint_stream(target, rate_hz, move |t| reference.value_at(t)).await?;
+
+    let this = Arc::clone(motor);
+    tokio::spawn(async move {
+        let mut samples = Vec::new();
+        loop {
+            select! {
+                result = rx.recv() => {
+                    let sample = match result {
+                        Ok(sample) => sample,
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    };
+                    let actual = match (target, sample.value) {
+                        (SetpointTarget::Speed, FeedbackValue::Speed(v)) | (SetpointTarget::Position, FeedbackValue::Position(v)) => v,
+                        _ => continue,
+                    };
+                    let time = start.elapsed().as_secs_f32();
+                    if let Some(reference) = trajectory.value_at(time) {
+                        samples.push(TrackingSample { time, reference, actual });
+                    }
+                }
+                _ = finished.wait() => break,
+            }
+        }
+        let completed = start.elapsed().as_secs_f32() >= trajectory.duration();
+        this.events.emit(MotorEvent::TrajectoryReport(TrackingReport::new(completed, samples)));
+    });
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use crate::error::MotorError;
+use crate::link_stats::LinkStats;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{timeout, Duration};
+
+/// 不支持 `get_version` 的旧固件所支持的指令
+const LEGACY_COMMANDS: &[&str] = &[
+    "get_speed", "get_position", "get_current", "get_udc", "get_config", "get_none",
+    "set_speed", "set_position", "stop", "save", "calibration",
+    "config_position_pid", "config_speed_pi", "config_current_pi", "config_idq_filter",
+    "config_encoder", "config_id", "config_udc",
+];
+
+const LEGACY_ENCODER_TYPES: &[&str] = &["MT6701"];
+
+/// 固件版本与能力
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirmwareInfo {
+    pub version: String,
+    pub build: String,
+    pub commands: Vec<String>,
+    pub encoder_types: Vec<String>,
+    /// 旧固件没有应答 `get_version`，能力按已知的旧版本处理
+    pub legacy: bool,
+}
+
+impl FirmwareInfo {
+    pub fn legacy() -> Self {
+        Self {
+            version: "legacy".into(),
+            build: String::new(),
+            commands: LEGACY_COMMANDS.iter().map(|s| s.to_string()).collect(),
+            encoder_types: LEGACY_ENCODER_TYPES.iter().map(|s| s.to_string()).collect(),
+            legacy: true,
+        }
+    }
+
+    pub fn supports(&self, command: &str) -> bool {
+        self.commands.iter().any(|c| c == command)
+    }
+
+    pub fn supports_encoder(&self, encoder_type: &str) -> bool {
+        self.encoder_types.iter().any(|t| t.eq_ignore_ascii_case(encoder_type))
+    }
+}
+
+/// 解析 `get_version` 的返回：
+/// ```text
+/// version: 1.3.0
+/// build: 2025-11-02
+/// commands: get_speed,get_position,...
+/// encoders: MT6701,AS5047
+/// ```
+#[derive(Debug, Default)]
+pub struct VersionParser {
+    version: Option<String>,
+    build: Option<String>,
+    commands: Option<Vec<String>>,
+    encoder_types: Option<Vec<String>>,
+}
+
+fn split_list(s: &str) -> Vec<String> {
+    s.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect()
+}
+
+impl VersionParser {
+    pub fn parse_line(&mut self, line: &str) {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            return;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "version" => self.version = Some(value.to_string()),
+            "build" => self.build = Some(value.to_string()),
+            "commands" => self.commands = Some(split_list(value)),
+            "encoders" => self.encoder_types = Some(split_list(value)),
+            _ => {}
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.version.is_some() && self.build.is_some() && self.commands.is_some() && self.encoder_types.is_some()
+    }
+
+    pub fn try_into_firmware_info(self) -> Result<FirmwareInfo, &'static str> {
+        Ok(FirmwareInfo {
+            version: self.version.ok_or("version")?,
+            build: self.build.ok_or("build")?,
+            commands: self.commands.ok_or("commands")?,
+            encoder_types: self.encoder_types.ok_or("encoders")?,
+            legacy: false,
+        })
+    }
+}
+
+/// 从串口接收版本信息
+pub async fn receive_version(rx: &mut broadcast::Receiver<String>, stats: &LinkStats, duration: Duration) -> Result<FirmwareInfo, MotorError> {
+    let mut parser = VersionParser::default();
+    let result = timeout(duration, async {
+        loop {
+            let line = match rx.recv().await {
+                Ok(line) => line,
+                Err(RecvError::Lagged(n)) => {
+                    stats.lagged.fetch_add(n, Ordering::Relaxed);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            parser.parse_line(&line);
+            if parser.is_complete() {
+                break;
+            }
+        }
+    }).await;
+    match result {
+        Ok(_) => parser.try_into_firmware_info().map_err(|field| MotorError::ParseError(format!("missing {field} in version info"))),
+        Err(_) => Err(MotorError::Timeout),
+    }
+}
@@ -0,0 +1,33 @@
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// DebugRun 时向下位机发送 keepalive 的间隔
+pub const KEEPALIVE_PERIOD: Duration = Duration::from_millis(200);
+
+/// 下位机超过该时间没有收到 keepalive 则自行停机
+pub const FIRMWARE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// 前端超过该时间没有心跳则由上位机停机。前端每 500ms 发送一次心跳，依赖窗口配置中关闭的
+/// `backgroundThrottling`；不支持该配置的平台上窗口隐藏时前端暂停监视，否则定时器降频会误停机
+pub const HOST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 前端心跳，收到第一次心跳后开始监视
+#[derive(Debug, Default)]
+pub struct HostHeartbeat {
+    last: Mutex<Option<Instant>>,
+}
+
+impl HostHeartbeat {
+    pub fn ping(&self) {
+        *self.last.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// 停止监视，直到下一次心跳
+    pub fn disarm(&self) {
+        *self.last.lock().unwrap() = None;
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.last.lock().unwrap().is_some_and(|last| last.elapsed() > HOST_TIMEOUT)
+    }
+}
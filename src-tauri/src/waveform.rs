@@ -0,0 +1,94 @@
+use crate::error::MotorError;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SetpointTarget {
+    Speed,
+    Position,
+}
+
+/// 设定值波形，时间单位为秒
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Waveform {
+    Sine { amplitude: f32, frequency: f32, offset: f32 },
+    Square { amplitude: f32, frequency: f32, offset: f32 },
+    Triangle { amplitude: f32, frequency: f32, offset: f32 },
+    /// 梯形往返：low -> high -> low，斜坡与两端保持时间可配置
+    Trapezoid { low: f32, high: f32, ramp_time: f32, hold_time: f32 },
+    /// 从 start 以 slope 变化，到达 end 后保持
+    Ramp { start: f32, end: f32, slope: f32 },
+}
+
+impl Waveform {
+    pub fn validate(&self) -> Result<(), MotorError> {
+        // NaN 与任何数比较都不成立，先于范围检查拒绝
+        let finite = |values: &[f32]| -> Result<(), MotorError> {
+            if values.iter().all(|v| v.is_finite()) {
+                Ok(())
+            } else {
+                Err(MotorError::InvalidArgument("waveform parameters must be finite".into()))
+            }
+        };
+        match *self {
+            Waveform::Sine { amplitude, frequency, offset }
+            | Waveform::Square { amplitude, frequency, offset }
+            | Waveform::Triangle { amplitude, frequency, offset } => {
+                finite(&[amplitude, frequency, offset])?;
+                if frequency <= 0f32 {
+                    return Err(MotorError::InvalidArgument("frequency must be positive".into()));
+                }
+            }
+            Waveform::Trapezoid { low, high, ramp_time, hold_time } => {
+                finite(&[low, high, ramp_time, hold_time])?;
+                if ramp_time < 0f32 || hold_time < 0f32 || ramp_time + hold_time <= 0f32 {
+                    return Err(MotorError::InvalidArgument("invalid trapezoid timing".into()));
+                }
+            }
+            Waveform::Ramp { start, end, slope } => {
+                finite(&[start, end, slope])?;
+                if slope <= 0f32 {
+                    return Err(MotorError::InvalidArgument("slope must be positive".into()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 计算 t 时刻的设定值
+    pub fn value_at(&self, t: f32) -> f32 {
+        match *self {
+            Waveform::Sine { amplitude, frequency, offset } => {
+                offset + amplitude * (2f32 * PI * frequency * t).sin()
+            }
+            Waveform::Square { amplitude, frequency, offset } => {
+                if (t * frequency).fract() < 0.5 { offset + amplitude } else { offset - amplitude }
+            }
+            Waveform::Triangle { amplitude, frequency, offset } => {
+                // 相位从 0 开始，与正弦波一致
+                let phase = (t * frequency + 0.25).fract();
+                offset + amplitude * (1f32 - 4f32 * (phase - 0.5).abs())
+            }
+            Waveform::Trapezoid { low, high, ramp_time, hold_time } => {
+                let period = 2f32 * (ramp_time + hold_time);
+                let t = t % period;
+                let ramp = |x: f32| if ramp_time > 0f32 { x / ramp_time } else { 1f32 };
+                let k = if t < ramp_time {
+                    ramp(t)
+                } else if t < ramp_time + hold_time {
+                    1f32
+                } else if t < 2f32 * ramp_time + hold_time {
+                    1f32 - ramp(t - ramp_time - hold_time)
+                } else {
+                    0f32
+                };
+                low + (high - low) * k
+            }
+            Waveform::Ramp { start, end, slope } => {
+                let delta = slope * t;
+                if end >= start { (start + delta).min(end) } else { (start - delta).max(end) }
+            }
+        }
+    }
+}
@@ -0,0 +1,470 @@
+//! 测试用的假固件，通过内存中的双工流代替串口，按脚本应答上位机的指令
+
+// 各测试文件只用到其中一部分
+#![allow(dead_code)]
+
+use ipmesctool_lib::command::MotorState;
+use ipmesctool_lib::events::{MemoryEventSink, MotorEvent};
+use ipmesctool_lib::feedback_parser::FeedbackValue;
+use ipmesctool_lib::firmware_image::IMAGE_CRC;
+use ipmesctool_lib::flasher::CHUNK_CRC;
+use ipmesctool_lib::frame::{Message, PROTOCOL_BINARY_ACK, PROTOCOL_TEXT_ACK};
+use ipmesctool_lib::line_framer::{FramingStats, Framed, LineFramer, MAX_LINE_LENGTH};
+use ipmesctool_lib::motor::{Motor, MotorFeedbackState};
+use ipmesctool_lib::serial::SerialDevice;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream, WriteHalf};
+use tokio::select;
+use tokio::sync::mpsc;
+use tokio::time::{interval, sleep, Duration, Instant};
+
+/// 等待条件成立的最长时间
+pub const WAIT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 反馈数据的发送周期
+const FEEDBACK_PERIOD: Duration = Duration::from_millis(2);
+
+const COMMANDS: &str = "get_speed,get_position,get_current,get_udc,get_config,get_none,get_version,get_state,\
+set_speed,set_position,stop,save,calibration,clear_fault,keepalive,protocol,\
+config_position_pid,config_speed_pi,config_current_pi,config_idq_filter,config_encoder,config_id,config_udc";
+
+const CONFIG_REPLY: &[&str] = &[
+    "id: 3",
+    "udc: 24.5",
+    "position_pid:",
+    "Kp: 1.5",
+    "Ki: 0.25",
+    "Kd: 0.05",
+    "maxoutput: 300",
+    "speed_pi:",
+    "Kp: 0.8",
+    "Ki: 0.01",
+    "maxoutput: 10",
+    "i_pi:",
+    "id_Kp: 0.3",
+    "id_Ki: 0.002",
+    "iq_Kp: 0.4",
+    "iq_Ki: 0.003",
+    "idq_filter_fc: 1000",
+    "encoder:",
+    "pole_pairs: 7",
+    "encoder_direct: -1",
+    "encoder_offset: 1.25",
+    "encoder_type: MT6701",
+];
+
+pub const CALIBRATION_OK: &[&str] = &[
+    "test ready.start test now.",
+    "pole_pairs read done",
+    "offset read done.",
+    "R read done",
+    "Ld read done",
+    "Lq read done",
+    "Speed PI set done",
+];
+
+/// 假固件的行为
+#[derive(Debug, Clone)]
+pub struct FirmwareScript {
+    /// 收到 calibration 后依次输出的行，输出完回到 Stop
+    pub calibration: Vec<&'static str>,
+    /// 校准输出每行的间隔
+    pub calibration_step: Duration,
+    /// 在第 n 条设定值指令（从 1 开始）尚未收完时发出该故障消息，用于制造故障与指令的竞争
+    pub fault_on_setpoint: Option<(usize, &'static str)>,
+    /// 双工流每个方向的缓冲字节数，较小时上位机写入会等待假固件读取
+    pub port_buffer: usize,
+    /// 启动时处于二进制协议，如上次会话没有切换回文本协议
+    pub binary: bool,
+    /// bootloader 对前 n 条 write 指令应答 CRC 错误
+    pub write_errors: usize,
+    /// 收到该指令后不应答，用于制造超时
+    pub silent_on: Option<&'static str>,
+    /// bootloader 擦除耗时，期间不处理后续指令
+    pub erase_time: Duration,
+}
+
+impl Default for FirmwareScript {
+    fn default() -> Self {
+        Self {
+            calibration: CALIBRATION_OK.to_vec(),
+            calibration_step: Duration::from_millis(5),
+            fault_on_setpoint: None,
+            port_buffer: 64 * 1024,
+            binary: false,
+            write_errors: 0,
+            silent_on: None,
+            erase_time: Duration::ZERO,
+        }
+    }
+}
+
+enum Control {
+    Send(String),
+    SetState(MotorState),
+    Unplug,
+}
+
+/// 进入 bootloader 后模拟的 Flash
+#[derive(Debug, Default)]
+struct Bootloader {
+    base_address: u32,
+    data: Vec<u8>,
+}
+
+/// 假固件的控制句柄
+pub struct FakeFirmware {
+    received: Arc<Mutex<Vec<String>>>,
+    flash: Arc<Mutex<Vec<u8>>>,
+    control: mpsc::UnboundedSender<Control>,
+}
+
+impl FakeFirmware {
+    pub fn spawn(stream: DuplexStream, script: FirmwareScript) -> Self {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let flash = Arc::new(Mutex::new(Vec::new()));
+        let (control, control_rx) = mpsc::unbounded_channel();
+        let (reader, writer) = tokio::io::split(stream);
+        let firmware = Firmware {
+            writer,
+            binary: script.binary,
+            script,
+            state: MotorState::Stop,
+            feedback: MotorFeedbackState::None,
+            setpoint: 0f32,
+            setpoints: 0,
+            calibration: VecDeque::new(),
+            bootloader: None,
+            flash: Arc::clone(&flash),
+            framer: LineFramer::new(MAX_LINE_LENGTH),
+            stats: FramingStats::default(),
+        };
+        tokio::spawn(firmware.run(reader, Arc::clone(&received), control_rx));
+        Self { received, flash, control }
+    }
+
+    /// 收到的指令，不含换行
+    pub fn received(&self) -> Vec<String> {
+        self.received.lock().unwrap().clone()
+    }
+
+    /// 最后一次 boot 时 Flash 中的内容
+    pub fn flash(&self) -> Vec<u8> {
+        self.flash.lock().unwrap().clone()
+    }
+
+    /// 主动发送一行，如故障消息
+    pub fn send_line(&self, line: &str) {
+        let _ = self.control.send(Control::Send(line.to_string()));
+    }
+
+    /// 不经过指令直接改变下位机状态，如下位机自行停机
+    pub fn set_state(&self, state: MotorState) {
+        let _ = self.control.send(Control::SetState(state));
+    }
+
+    /// 关闭流，相当于拔出串口
+    pub fn unplug(&self) {
+        let _ = self.control.send(Control::Unplug);
+    }
+}
+
+struct Firmware {
+    writer: WriteHalf<DuplexStream>,
+    script: FirmwareScript,
+    state: MotorState,
+    feedback: MotorFeedbackState,
+    setpoint: f32,
+    /// 已收到的设定值指令数
+    setpoints: usize,
+    binary: bool,
+    calibration: VecDeque<&'static str>,
+    bootloader: Option<Bootloader>,
+    flash: Arc<Mutex<Vec<u8>>>,
+    framer: LineFramer,
+    stats: FramingStats,
+}
+
+impl Firmware {
+    async fn run(mut self, mut reader: tokio::io::ReadHalf<DuplexStream>, received: Arc<Mutex<Vec<String>>>, mut control_rx: mpsc::UnboundedReceiver<Control>) {
+        let mut buf = [0u8; 256];
+        let mut cache = Vec::new();
+        let mut feedback_tick = interval(FEEDBACK_PERIOD);
+        let mut calibration_tick = interval(self.script.calibration_step);
+        loop {
+            // 先处理控制消息，保证测试中先于随后的指令生效
+            select! {
+                biased;
+                control = control_rx.recv() => match control {
+                    Some(Control::Send(line)) => self.write_line(&line).await,
+                    Some(Control::SetState(state)) => self.state = state,
+                    Some(Control::Unplug) | None => return,
+                },
+                result = reader.read(&mut buf) => {
+                    let n = match result {
+                        Ok(n) if n > 0 => n,
+                        _ => return,
+                    };
+                    cache.extend_from_slice(&buf[..n]);
+                    if self.inject_fault(&cache).await {
+                        // 暂停读取，让上位机在写入完成前处理故障
+                        sleep(Duration::from_millis(50)).await;
+                    }
+                    // 每次只取一条指令，协议可能在两条指令之间切换
+                    while let Some(command) = self.next_command(&mut cache) {
+                        received.lock().unwrap().push(command.clone());
+                        self.handle(&command).await;
+                    }
+                }
+                _ = feedback_tick.tick() => self.write_feedback().await,
+                _ = calibration_tick.tick(), if !self.calibration.is_empty() => {
+                    let line = self.calibration.pop_front().unwrap();
+                    self.write_line(line).await;
+                    if self.calibration.is_empty() {
+                        self.state = MotorState::Stop;
+                    }
+                }
+            }
+        }
+    }
+
+    fn next_command(&mut self, cache: &mut Vec<u8>) -> Option<String> {
+        loop {
+            if self.binary {
+                let skip = self.framer.pending_lf(cache);
+                cache.drain(..skip);
+                let pos = cache.iter().position(|b| *b == 0)?;
+                let frame: Vec<u8> = cache.drain(..=pos).collect();
+                let command = match Message::decode(&frame[..pos]).expect("host sent a corrupted frame") {
+                    Message::Text(line) => line,
+                    Message::SetSpeed(speed) => format!("set_speed {speed}"),
+                    Message::SetPosition(position) => format!("set_position {position}"),
+                    Message::Stop => "stop".to_string(),
+                    Message::Feedback(value) => panic!("host sent feedback {value:?}"),
+                };
+                return Some(command);
+            }
+            let (consumed, framed) = self.framer.feed(cache, &self.stats);
+            cache.drain(..consumed);
+            match framed? {
+                Framed::Line(line) if !line.trim().is_empty() => return Some(line.trim().to_string()),
+                _ => continue,
+            }
+        }
+    }
+
+    async fn inject_fault(&mut self, cache: &[u8]) -> bool {
+        let Some((n, line)) = self.script.fault_on_setpoint else {
+            return false;
+        };
+        let partial = cache.starts_with(b"set_") && !cache.contains(&b'\n');
+        if !partial || self.setpoints + 1 != n {
+            return false;
+        }
+        self.script.fault_on_setpoint = None;
+        self.state = MotorState::Fault;
+        self.write_line(line).await;
+        true
+    }
+
+    async fn handle(&mut self, command: &str) {
+        let mut parts = command.split_whitespace();
+        let name = parts.next().unwrap_or_default();
+        if self.script.silent_on == Some(name) {
+            return;
+        }
+        if self.bootloader.is_some() {
+            if name == "erase" {
+                sleep(self.script.erase_time).await;
+            }
+            let reply = self.handle_bootloader(name, parts.collect());
+            return self.write_line(&reply).await;
+        }
+        let arg = parts.next();
+        match name {
+            "get_version" => {
+                self.write_line("version: 1.4.0").await;
+                self.write_line("build: test").await;
+                self.write_line(&format!("commands: {COMMANDS}")).await;
+                self.write_line("encoders: MT6701").await;
+            }
+            "get_config" => {
+                for line in CONFIG_REPLY {
+                    self.write_line(line).await;
+                }
+            }
+            "get_state" => self.write_line(&format!("state: {}", self.state)).await,
+            "get_speed" => self.feedback = MotorFeedbackState::Speed,
+            "get_position" => self.feedback = MotorFeedbackState::Position,
+            "get_current" => self.feedback = MotorFeedbackState::Current,
+            "get_udc" => self.feedback = MotorFeedbackState::Udc,
+            "get_none" => self.feedback = MotorFeedbackState::None,
+            "set_speed" | "set_position" => {
+                self.setpoint = arg.and_then(|v| v.parse().ok()).expect("missing setpoint");
+                self.setpoints += 1;
+                self.state = MotorState::DebugRun;
+                self.feedback = if name == "set_speed" { MotorFeedbackState::Speed } else { MotorFeedbackState::Position };
+            }
+            "stop" => {
+                self.state = MotorState::Stop;
+                self.feedback = MotorFeedbackState::None;
+                self.calibration.clear();
+            }
+            "calibration" => {
+                self.state = MotorState::Test;
+                self.calibration = self.script.calibration.iter().copied().collect();
+            }
+            "clear_fault" => {
+                self.state = MotorState::Stop;
+                self.write_line("fault: cleared").await;
+            }
+            // 应答使用切换前的协议
+            "protocol" => match arg {
+                Some("binary") => {
+                    self.write_line(PROTOCOL_BINARY_ACK).await;
+                    self.binary = true;
+                }
+                Some("text") => {
+                    self.write_line(PROTOCOL_TEXT_ACK).await;
+                    self.binary = false;
+                }
+                _ => self.write_line("unknown protocol").await,
+            },
+            "save" => self.write_line("save done").await,
+            "bootloader" => {
+                self.feedback = MotorFeedbackState::None;
+                self.bootloader = Some(Bootloader::default());
+                self.write_line("bootloader ready").await;
+            }
+            "keepalive" => {}
+            _ if name.starts_with("config_") => self.write_line(&format!("{name} ok")).await,
+            _ => self.write_line(&format!("unknown command: {name}")).await,
+        }
+    }
+
+    /// bootloader 指令，返回应答
+    fn handle_bootloader(&mut self, name: &str, args: Vec<&str>) -> String {
+        let hex = |i: usize| u32::from_str_radix(args[i], 16).expect("invalid hex argument");
+        let flash = self.bootloader.as_mut().unwrap();
+        match name {
+            "erase" => {
+                flash.base_address = hex(0);
+                flash.data = vec![0xff; args[1].parse().expect("invalid size")];
+                "ok".into()
+            }
+            "write" => {
+                let offset = (hex(0) - flash.base_address) as usize;
+                let data: Vec<u8> = (0..args[1].len()).step_by(2)
+                    .map(|i| u8::from_str_radix(&args[1][i..i + 2], 16).expect("invalid data"))
+                    .collect();
+                if self.script.write_errors > 0 || CHUNK_CRC.checksum(&data) != hex(2) as u16 {
+                    self.script.write_errors = self.script.write_errors.saturating_sub(1);
+                    return "err crc mismatch".into();
+                }
+                flash.data[offset..offset + data.len()].copy_from_slice(&data);
+                "ok".into()
+            }
+            "verify" => {
+                if IMAGE_CRC.checksum(&flash.data) == hex(2) { "ok".into() } else { "err verify failed".into() }
+            }
+            "boot" => {
+                *self.flash.lock().unwrap() = self.bootloader.take().unwrap().data;
+                self.state = MotorState::Stop;
+                "ok".into()
+            }
+            _ => format!("err unknown command {name}"),
+        }
+    }
+
+    async fn write_line(&mut self, line: &str) {
+        let bytes = if self.binary {
+            Message::Text(line.to_string()).encode()
+        } else {
+            format!("{line}\r\n").into_bytes()
+        };
+        // 上位机已断开时忽略
+        let _ = self.writer.write_all(&bytes).await;
+    }
+
+    async fn write_feedback(&mut self) {
+        let value = match self.feedback {
+            MotorFeedbackState::None => return,
+            MotorFeedbackState::Speed => FeedbackValue::Speed(self.setpoint),
+            MotorFeedbackState::Position => FeedbackValue::Position(self.setpoint),
+            MotorFeedbackState::Current => FeedbackValue::Current(0.5, -0.25, -0.25),
+            MotorFeedbackState::Udc => FeedbackValue::Udc(24.5),
+        };
+        if self.binary {
+            let _ = self.writer.write_all(&Message::Feedback(value).encode()).await;
+            return;
+        }
+        let line = match value {
+            FeedbackValue::Speed(v) => format!("speed: {v}"),
+            FeedbackValue::Position(v) => format!("position: {v}"),
+            FeedbackValue::Current(a, b, c) => format!("iabc:{a},{b},{c}"),
+            FeedbackValue::Udc(v) => format!("udc: {v}"),
+        };
+        self.write_line(&line).await;
+    }
+}
+
+/// 连接到假固件的电机
+pub struct TestMotor {
+    pub motor: Arc<Motor>,
+    pub firmware: FakeFirmware,
+    events: Arc<MemoryEventSink>,
+    seen: Mutex<Vec<MotorEvent>>,
+}
+
+impl TestMotor {
+    pub async fn connect(script: FirmwareScript) -> Self {
+        let (host, device) = tokio::io::duplex(script.port_buffer);
+        let firmware = FakeFirmware::spawn(device, script);
+        let serial = SerialDevice::new("fake".into(), 115200);
+        serial.attach(Box::new(host)).await;
+        let events = Arc::new(MemoryEventSink::default());
+        let motor = Motor::new(serial, events.clone());
+        motor.start_parse_feedback_loop().await;
+        Self { motor, firmware, events, seen: Mutex::new(Vec::new()) }
+    }
+
+    /// 按正常连接流程打开，后台任务都在运行
+    pub async fn open(script: FirmwareScript) -> Self {
+        let (host, device) = tokio::io::duplex(script.port_buffer);
+        let firmware = FakeFirmware::spawn(device, script);
+        let serial = SerialDevice::new("fake".into(), 115200);
+        serial.attach(Box::new(host)).await;
+        let events = Arc::new(MemoryEventSink::default());
+        let motor = Motor::open(serial, events.clone(), None).await.unwrap();
+        Self { motor, firmware, events, seen: Mutex::new(Vec::new()) }
+    }
+
+    /// 到目前为止收到的所有事件
+    pub fn events(&self) -> Vec<MotorEvent> {
+        let mut seen = self.seen.lock().unwrap();
+        seen.extend(self.events.take());
+        seen.clone()
+    }
+
+    /// 等待满足条件的事件，超时则测试失败
+    pub async fn wait_event(&self, what: &str, pred: impl Fn(&MotorEvent) -> bool) -> MotorEvent {
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+        loop {
+            if let Some(event) = self.events().into_iter().find(&pred) {
+                return event;
+            }
+            assert!(Instant::now() < deadline, "timed out waiting for {what}");
+            sleep(Duration::from_millis(5)).await;
+        }
+    }
+}
+
+/// 等待条件成立，超时则测试失败
+pub async fn wait_until(what: &str, mut cond: impl FnMut() -> bool) {
+    let deadline = Instant::now() + WAIT_TIMEOUT;
+    while !cond() {
+        assert!(Instant::now() < deadline, "timed out waiting for {what}");
+        sleep(Duration::from_millis(5)).await;
+    }
+}
@@ -0,0 +1,21 @@
+//! 急停结果的确认规则
+
+use ipmesctool_lib::command::MotorState;
+use ipmesctool_lib::estop::{DeviceStopResult, EmergencyStopReport};
+
+fn device(sent: bool, state: Option<MotorState>) -> DeviceStopResult {
+    DeviceStopResult { port_name: "COM3".into(), sent, error: None, state }
+}
+
+#[test]
+fn only_queried_stopped_devices_are_confirmed() {
+    assert!(device(true, Some(MotorState::Stop)).is_confirmed());
+    assert!(device(true, Some(MotorState::Fault)).is_confirmed());
+    assert!(!device(true, Some(MotorState::DebugRun)).is_confirmed());
+    assert!(!device(false, Some(MotorState::Stop)).is_confirmed());
+    // 状态未知时不能认为已停机
+    assert!(!device(true, None).is_confirmed());
+
+    let report = EmergencyStopReport { devices: vec![device(true, Some(MotorState::Stop)), device(true, None)] };
+    assert!(!report.is_confirmed());
+}
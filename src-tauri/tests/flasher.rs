@@ -0,0 +1,101 @@
+//! 通过假固件的 bootloader 测试固件烧录
+
+mod common;
+
+use common::{FirmwareScript, TestMotor};
+use ipmesctool_lib::error::MotorError;
+use ipmesctool_lib::events::MotorEvent;
+use ipmesctool_lib::firmware_image::FirmwareImage;
+use ipmesctool_lib::flasher::{flash_firmware, FlashStage, CHUNK_SIZE};
+use ipmesctool_lib::motor::STATE_SYNC_PERIOD;
+use std::time::Duration;
+
+/// 跨越多个数据块、最后一块不满的镜像
+fn image() -> FirmwareImage {
+    let data = (0..CHUNK_SIZE * 2 + 10).map(|i| (i * 7) as u8).collect();
+    FirmwareImage::from_binary(data, 0x0800_4000).unwrap()
+}
+
+/// 收到的指令名
+fn command_names(t: &TestMotor) -> Vec<String> {
+    t.firmware.received().iter()
+        .map(|c| c.split_whitespace().next().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn flash_writes_image_and_reboots() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    let image = image();
+    flash_firmware(&t.motor.serial, &image, t.motor.events.as_ref()).await.unwrap();
+
+    assert_eq!(t.firmware.flash(), image.data);
+    assert_eq!(command_names(&t), ["bootloader", "erase", "write", "write", "write", "verify", "boot"]);
+    assert_eq!(t.firmware.received()[1], format!("erase 08004000 {}", image.data.len()));
+    let stages: Vec<_> = t.events().into_iter()
+        .filter_map(|e| match e {
+            MotorEvent::FlashProgress(progress) => Some((progress.stage, progress.written)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(stages.first(), Some(&(FlashStage::EnterBootloader, 0)));
+    assert_eq!(stages.last(), Some(&(FlashStage::Done, image.data.len())));
+}
+
+#[tokio::test]
+async fn flash_switches_back_to_text_protocol_first() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.query_firmware().await.unwrap();
+    t.motor.negotiate_protocol().await.unwrap();
+    let image = image();
+    flash_firmware(&t.motor.serial, &image, t.motor.events.as_ref()).await.unwrap();
+
+    assert_eq!(t.firmware.flash(), image.data);
+    assert_eq!(&t.firmware.received()[..4], ["get_version", "protocol binary", "protocol text", "bootloader"]);
+}
+
+#[tokio::test]
+async fn crc_error_is_retried() {
+    let t = TestMotor::connect(FirmwareScript { write_errors: 2, ..Default::default() }).await;
+    let image = image();
+    flash_firmware(&t.motor.serial, &image, t.motor.events.as_ref()).await.unwrap();
+
+    assert_eq!(t.firmware.flash(), image.data);
+    // 第一块重试两次
+    let writes: Vec<_> = t.firmware.received().into_iter().filter(|c| c.starts_with("write")).collect();
+    assert_eq!(writes.len(), 5);
+    assert_eq!(writes[0], writes[2]);
+}
+
+#[tokio::test]
+async fn crc_error_beyond_retries_fails() {
+    let t = TestMotor::connect(FirmwareScript { write_errors: 4, ..Default::default() }).await;
+    let result = flash_firmware(&t.motor.serial, &image(), t.motor.events.as_ref()).await;
+
+    assert!(matches!(result, Err(MotorError::FlashError(ref e)) if e == "crc mismatch"), "{result:?}");
+    assert!(!command_names(&t).contains(&"verify".to_string()));
+    assert!(t.firmware.flash().is_empty());
+}
+
+#[tokio::test]
+async fn missing_bootloader_reply_times_out() {
+    let t = TestMotor::connect(FirmwareScript { silent_on: Some("bootloader"), ..Default::default() }).await;
+    let result = flash_firmware(&t.motor.serial, &image(), t.motor.events.as_ref()).await;
+
+    assert!(matches!(result, Err(MotorError::Timeout)), "{result:?}");
+    assert_eq!(command_names(&t), ["bootloader"]);
+}
+
+#[tokio::test]
+async fn flash_after_open_stops_background_tasks() {
+    // 擦除期间状态同步到期，未停止时其指令会被 bootloader 拒绝
+    let erase_time = STATE_SYNC_PERIOD + Duration::from_millis(500);
+    let t = TestMotor::open(FirmwareScript { erase_time, ..Default::default() }).await;
+    let image = image();
+    t.motor.flash_firmware(&image).await.unwrap();
+
+    assert_eq!(t.firmware.flash(), image.data);
+    let names = command_names(&t);
+    let start = names.iter().position(|n| n == "bootloader").unwrap();
+    assert_eq!(&names[start..], ["bootloader", "erase", "write", "write", "write", "verify", "boot"]);
+}
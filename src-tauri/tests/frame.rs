@@ -0,0 +1,80 @@
+//! 二进制协议的 COBS 与 CRC 编解码测试
+
+use ipmesctool_lib::error::MotorError;
+use ipmesctool_lib::feedback_parser::FeedbackValue;
+use ipmesctool_lib::frame::{cobs_decode, cobs_encode, Message};
+
+/// 去掉结尾 0x00 后解码
+fn decode(frame: &[u8]) -> Result<Message, MotorError> {
+    assert_eq!(frame.last(), Some(&0));
+    Message::decode(&frame[..frame.len() - 1])
+}
+
+#[test]
+fn messages_round_trip() {
+    let messages = [
+        Message::Text("speed: 1.5".into()),
+        Message::SetSpeed(-120.0),
+        Message::SetPosition(3.25),
+        Message::Stop,
+        Message::Feedback(FeedbackValue::Speed(0.0)),
+        Message::Feedback(FeedbackValue::Position(-1.0)),
+        Message::Feedback(FeedbackValue::Current(0.5, -0.25, 0.0)),
+        Message::Feedback(FeedbackValue::Udc(24.0)),
+    ];
+    for message in messages {
+        let frame = message.encode();
+        // 分隔符只出现在结尾
+        assert!(!frame[..frame.len() - 1].contains(&0), "{message:?}");
+        assert_eq!(decode(&frame).unwrap(), message);
+    }
+}
+
+#[test]
+fn long_text_round_trips() {
+    // get_version 的 commands 行超过 256 字节
+    let line = format!("commands: {}", vec!["set_speed"; 40].join(" "));
+    assert!(line.len() > 256);
+    let message = Message::Text(line);
+
+    assert_eq!(decode(&message.encode()).unwrap(), message);
+}
+
+#[test]
+fn cobs_block_boundary() {
+    for len in [253, 254, 255, 508, 509] {
+        let data: Vec<u8> = (0..len).map(|i| (i % 255 + 1) as u8).collect();
+        let encoded = cobs_encode(&data);
+
+        assert!(!encoded.contains(&0), "len {len}");
+        assert_eq!(cobs_decode(&encoded).unwrap(), data, "len {len}");
+    }
+    // 恰好 254 个非零字节占满一个块，后面跟一个空块
+    let encoded = cobs_encode(&[1; 254]);
+    assert_eq!(encoded.len(), 256);
+    assert_eq!((encoded[0], encoded[255]), (0xff, 0x01));
+}
+
+#[test]
+fn cobs_keeps_zero_bytes() {
+    for data in [vec![0], vec![0, 0], vec![1, 0, 2], vec![0; 300]] {
+        assert_eq!(cobs_decode(&cobs_encode(&data)).unwrap(), data);
+    }
+}
+
+#[test]
+fn wrong_crc_is_rejected() {
+    let mut frame = Message::Text("speed: 1.5".into()).encode();
+    // 帧头为 COBS 码和消息 id，改动其后的文本，保持 COBS 结构有效
+    frame[2] = b'S';
+
+    assert!(matches!(decode(&frame), Err(MotorError::ParseError(e)) if e.contains("CRC")));
+}
+
+#[test]
+fn truncated_frame_is_rejected() {
+    let frame = Message::Feedback(FeedbackValue::Current(1.0, 2.0, 3.0)).encode();
+    for len in 1..frame.len() - 1 {
+        assert!(Message::decode(&frame[..len]).is_err(), "accepted {len} of {} bytes", frame.len() - 1);
+    }
+}
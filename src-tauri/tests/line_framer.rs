@@ -0,0 +1,100 @@
+//! LineFramer 的分行测试
+
+use ipmesctool_lib::line_framer::{FramingStats, Framed, LineFramer};
+use std::sync::atomic::Ordering::Relaxed;
+
+/// 依次喂入每段数据，返回得到的全部结果
+fn feed_all(framer: &mut LineFramer, chunks: &[&[u8]], stats: &FramingStats) -> Vec<Framed> {
+    let mut out = Vec::new();
+    for chunk in chunks {
+        let mut data = *chunk;
+        while !data.is_empty() {
+            let (consumed, framed) = framer.feed(data, stats);
+            data = &data[consumed..];
+            out.extend(framed);
+        }
+    }
+    out
+}
+
+fn line(s: &str) -> Framed {
+    Framed::Line(s.to_string())
+}
+
+#[test]
+fn crlf_split_across_reads_is_one_line_end() {
+    let stats = FramingStats::default();
+    let mut framer = LineFramer::new(64);
+    let out = feed_all(&mut framer, &[b"speed: 1\r", b"\nspeed: 2\r", b"\n"], &stats);
+
+    assert_eq!(out, [line("speed: 1"), line("speed: 2")]);
+    assert_eq!(stats.lines.load(Relaxed), 2);
+}
+
+#[test]
+fn bare_cr_and_lf_end_lines() {
+    let stats = FramingStats::default();
+    let mut framer = LineFramer::new(64);
+    let out = feed_all(&mut framer, &[b"a\rb\nc\r\r"], &stats);
+
+    // 连续两个 `\r` 之间是一个空行
+    assert_eq!(out, [line("a"), line("b"), line("c"), line("")]);
+    assert_eq!(stats.lines.load(Relaxed), 3);
+}
+
+#[test]
+fn multibyte_character_split_across_reads() {
+    let stats = FramingStats::default();
+    let mut framer = LineFramer::new(64);
+    let text = "温度".as_bytes();
+    let out = feed_all(&mut framer, &[&text[..2], &text[2..], b"\n"], &stats);
+
+    assert_eq!(out, [line("温度")]);
+    assert_eq!(stats.invalid_utf8.load(Relaxed), 0);
+}
+
+#[test]
+fn overlong_line_is_dropped_and_counted() {
+    let stats = FramingStats::default();
+    let mut framer = LineFramer::new(4);
+    let out = feed_all(&mut framer, &[b"abcdef", b"gh\nok\n"], &stats);
+
+    assert_eq!(out, [Framed::Overflow(8), line("ok")]);
+    assert_eq!(stats.overflows.load(Relaxed), 1);
+    assert_eq!(stats.overflow_bytes.load(Relaxed), 8);
+    assert_eq!(stats.lines.load(Relaxed), 1);
+}
+
+#[test]
+fn invalid_utf8_is_escaped_and_counted() {
+    let stats = FramingStats::default();
+    let mut framer = LineFramer::new(64);
+    let out = feed_all(&mut framer, &[b"udc: \xff24\n"], &stats);
+
+    assert_eq!(out, [line("udc: \\xff24")]);
+    assert_eq!(stats.invalid_utf8.load(Relaxed), 1);
+}
+
+#[test]
+fn pending_lf_after_protocol_switch() {
+    let stats = FramingStats::default();
+    let mut framer = LineFramer::new(64);
+    let (consumed, framed) = framer.feed(b"protocol: binary\r", &stats);
+    assert_eq!((consumed, framed), (17, Some(line("protocol: binary"))));
+
+    // `\n` 还没有收到时不跳过，也不清除记录
+    assert_eq!(framer.pending_lf(b""), 0);
+    assert_eq!(framer.pending_lf(b"\n\x01\x02"), 1);
+    // 只跳过一次
+    assert_eq!(framer.pending_lf(b"\n"), 0);
+}
+
+#[test]
+fn stray_binary_frame_is_dropped() {
+    let stats = FramingStats::default();
+    let mut framer = LineFramer::new(64);
+    let out = feed_all(&mut framer, &[b"\x12\x01protocol", b": text\xae\xaa\x00version: 1.4.0\r\n"], &stats);
+
+    assert_eq!(out, [line("version: 1.4.0")]);
+    assert_eq!(stats.invalid_utf8.load(Relaxed), 0);
+}
@@ -0,0 +1,393 @@
+//! 通过假固件端到端地测试 Motor 与 SerialDevice
+
+mod common;
+
+use common::{wait_until, FirmwareScript, TestMotor, CALIBRATION_OK, WAIT_TIMEOUT};
+use ipmesctool_lib::calibration_parser::ParserState;
+use ipmesctool_lib::command::{MotorConfigCommand, MotorRunCommand, MotorState};
+use ipmesctool_lib::config_parser::{EncoderDirection, EncoderType};
+use ipmesctool_lib::error::MotorError;
+use ipmesctool_lib::events::MotorEvent;
+use ipmesctool_lib::fault_parser::FaultCode;
+use ipmesctool_lib::feedback_parser::FeedbackValue;
+use ipmesctool_lib::frame::Protocol;
+use ipmesctool_lib::motor::{MotorFeedbackState, Timestamped};
+use ipmesctool_lib::safety::{SafetyAction, SafetyKind, SafetyLimits};
+use ipmesctool_lib::sweep::{run_gain_sweep, SweepGrid, SweepLoop, SweepRequest};
+use ipmesctool_lib::waveform::SetpointTarget;
+use std::sync::atomic::Ordering::Relaxed;
+use tokio::sync::broadcast;
+use tokio::time::{sleep, timeout, Duration};
+
+/// 等待下一条满足条件的反馈
+async fn next_feedback(rx: &mut broadcast::Receiver<Timestamped<FeedbackValue>>, pred: impl Fn(&FeedbackValue) -> bool) -> FeedbackValue {
+    timeout(WAIT_TIMEOUT, async {
+        loop {
+            let sample = rx.recv().await.expect("feedback channel closed");
+            if pred(&sample.value) {
+                return sample.value;
+            }
+        }
+    }).await.expect("timed out waiting for feedback")
+}
+
+#[tokio::test]
+async fn load_config_parses_firmware_reply() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    let config = t.motor.load_config().await.unwrap();
+
+    assert_eq!(config.id, 3);
+    assert_eq!(config.udc, 24.5);
+    assert_eq!((config.position_pid.kp, config.position_pid.ki, config.position_pid.kd), (1.5, 0.25, 0.05));
+    assert_eq!(config.position_pid.output_max, 300.0);
+    assert_eq!((config.speed_pi.kp, config.speed_pi.ki, config.speed_pi.output_max), (0.8, 0.01, 10.0));
+    assert_eq!((config.current_id_pi.kp, config.current_iq_pi.ki), (0.3, 0.003));
+    assert_eq!(config.fc, 1000.0);
+    assert_eq!(config.encoder_config.pole_pairs, 7);
+    assert_eq!(config.encoder_config.encoder_direction, EncoderDirection::Reverse);
+    assert!(matches!(config.encoder_config.encoder_type, EncoderType::MT6701));
+    assert_eq!(t.firmware.received(), ["get_config"]);
+    assert!(t.motor.motor_config.lock().await.is_some());
+}
+
+#[tokio::test]
+async fn calibration_reports_progress_and_returns_to_stop() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.calibration().await.unwrap();
+
+    assert_eq!(t.motor.state(), MotorState::Stop);
+    assert!(t.motor.unsaved.load(Relaxed));
+    let progress: Vec<_> = t.events().into_iter()
+        .filter_map(|e| match e {
+            MotorEvent::CalibrationProgress(state) => Some(state),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(progress.len(), CALIBRATION_OK.len());
+    assert_eq!(progress.last(), Some(&ParserState::Done));
+    let states: Vec<_> = t.events().into_iter()
+        .filter_map(|e| match e {
+            MotorEvent::StateChanged(state) => Some(state),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(states, [MotorState::Test, MotorState::Stop]);
+}
+
+#[tokio::test]
+async fn calibration_failure_is_reported() {
+    let script = FirmwareScript {
+        calibration: vec!["test ready.start test now.", "pole_pairs read done", "offset read failed."],
+        ..Default::default()
+    };
+    let t = TestMotor::connect(script).await;
+    let result = t.motor.calibration().await;
+
+    assert!(matches!(result, Err(MotorError::CalibrationError(ref e)) if e == "offset read failed"), "{result:?}");
+    assert_eq!(t.motor.state(), MotorState::Stop);
+}
+
+#[tokio::test]
+async fn device_log_skips_request_replies() {
+    let script = FirmwareScript {
+        calibration: vec!["test ready.start test now.", "pole_pairs read done", "offset read failed."],
+        ..Default::default()
+    };
+    let t = TestMotor::connect(script).await;
+    t.motor.load_config().await.unwrap();
+    let _ = t.motor.calibration().await;
+    t.motor.send_config_command(&MotorConfigCommand::ConfigUdc(24.0)).await.unwrap();
+    t.wait_event("config reply", |e| matches!(e, MotorEvent::SerialReceived(line) if line == "config_udc ok")).await;
+    sleep(Duration::from_millis(400)).await;
+    t.firmware.send_line("warn: temperature 70C");
+    t.firmware.send_line("fault: overcurrent");
+    t.wait_event("fault log", |e| matches!(e, MotorEvent::DeviceLog(log) if log.message == "fault: overcurrent")).await;
+
+    let logs: Vec<_> = t.events().into_iter()
+        .filter_map(|e| match e {
+            MotorEvent::DeviceLog(log) => Some(log.message),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(logs, ["warn: temperature 70C", "fault: overcurrent"]);
+}
+
+#[tokio::test]
+async fn stop_interrupts_calibration() {
+    let script = FirmwareScript { calibration_step: Duration::from_millis(200), ..Default::default() };
+    let t = TestMotor::connect(script).await;
+    let motor = t.motor.clone();
+    let calibration = tokio::spawn(async move { motor.calibration().await });
+
+    wait_until("calibration to start", || t.motor.state() == MotorState::Test).await;
+    t.motor.send_running_command(&MotorRunCommand::Stop).await.unwrap();
+    let result = timeout(WAIT_TIMEOUT, calibration).await.unwrap().unwrap();
+
+    assert!(matches!(result, Err(MotorError::CalibrationError(_))), "{result:?}");
+    assert_eq!(t.motor.state(), MotorState::Stop);
+    assert_eq!(t.firmware.received(), ["calibration", "stop"]);
+}
+
+#[tokio::test]
+async fn set_feedback_switches_parsed_feedback() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    let mut rx = t.motor.feedback_tx.subscribe();
+    t.motor.send_running_command(&MotorRunCommand::SetSpeed(50.0)).await.unwrap();
+
+    assert_eq!(t.motor.state(), MotorState::DebugRun);
+    let value = next_feedback(&mut rx, |_| true).await;
+    assert_eq!(value, FeedbackValue::Speed(50.0));
+
+    t.motor.set_feedback(MotorFeedbackState::Current).await.unwrap();
+    let value = next_feedback(&mut rx, |v| matches!(v, FeedbackValue::Current(..))).await;
+    assert_eq!(value, FeedbackValue::Current(0.5, -0.25, -0.25));
+    assert_eq!(t.firmware.received(), ["set_speed 50", "get_current"]);
+
+    // 反馈数据不转发到控制台
+    let forwarded = t.events().into_iter()
+        .any(|e| matches!(e, MotorEvent::SerialReceived(line) if line.starts_with("speed:") || line.starts_with("iabc:")));
+    assert!(!forwarded);
+    assert!(t.motor.serial.stats.feedback_received.load(Relaxed) > 0);
+    assert_eq!(t.motor.serial.stats.parse_failures.load(Relaxed), 0);
+
+    t.motor.send_running_command(&MotorRunCommand::Stop).await.unwrap();
+    assert_eq!(t.motor.state(), MotorState::Stop);
+}
+
+#[tokio::test]
+async fn fault_message_blocks_run_until_cleared() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.send_running_command(&MotorRunCommand::SetSpeed(10.0)).await.unwrap();
+    t.firmware.send_line("fault: overcurrent");
+
+    let event = t.wait_event("fault", |e| matches!(e, MotorEvent::Fault(_))).await;
+    let MotorEvent::Fault(record) = event else { unreachable!() };
+    assert_eq!(record.code, FaultCode::Overcurrent);
+    assert_eq!(record.previous_state, MotorState::DebugRun);
+    assert_eq!(t.motor.state(), MotorState::Fault);
+
+    let result = t.motor.send_running_command(&MotorRunCommand::SetSpeed(10.0)).await;
+    assert!(matches!(result, Err(MotorError::FaultDetected(_))), "{result:?}");
+    t.motor.clear_fault().await.unwrap();
+    assert_eq!(t.motor.state(), MotorState::Stop);
+    t.motor.send_running_command(&MotorRunCommand::SetSpeed(10.0)).await.unwrap();
+}
+
+#[tokio::test]
+async fn fault_during_setpoint_stream_is_not_overwritten() {
+    // 第 5 条设定值写入途中收到故障，写入完成后不能再把状态改回 DebugRun
+    let script = FirmwareScript { fault_on_setpoint: Some((5, "fault: overcurrent")), port_buffer: 4, ..Default::default() };
+    let t = TestMotor::connect(script).await;
+    let finished = t.motor.start_setpoint_stream(SetpointTarget::Speed, 100.0, |_| Some(10.0)).await.unwrap();
+    timeout(WAIT_TIMEOUT, finished.wait()).await.expect("stream did not stop on fault");
+
+    assert_eq!(t.motor.state(), MotorState::Fault);
+    t.wait_event("fault", |e| matches!(e, MotorEvent::Fault(_))).await;
+    let result = t.motor.send_running_command(&MotorRunCommand::SetSpeed(10.0)).await;
+    assert!(matches!(result, Err(MotorError::FaultDetected(_))), "{result:?}");
+    let last_state = t.events().into_iter().rev().find_map(|e| match e {
+        MotorEvent::StateChanged(state) => Some(state),
+        _ => None,
+    });
+    assert_eq!(last_state, Some(MotorState::Fault));
+}
+
+#[tokio::test]
+async fn emergency_stop_latch_blocks_queued_run_commands() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.send_running_command(&MotorRunCommand::SetSpeed(10.0)).await.unwrap();
+    t.motor.serial.emergency_stop("stop\r\n").await.unwrap();
+    t.motor.mark_emergency_stopped();
+
+    let result = t.motor.send_running_command(&MotorRunCommand::SetSpeed(10.0)).await;
+    assert!(matches!(result, Err(MotorError::EmergencyStopped)), "{result:?}");
+    assert_eq!(t.motor.state(), MotorState::Stop);
+    t.motor.send_running_command(&MotorRunCommand::Stop).await.unwrap();
+    wait_until("stop to arrive", || t.firmware.received().len() == 3).await;
+    assert_eq!(t.firmware.received(), ["set_speed 10", "stop", "stop"]);
+}
+
+#[tokio::test]
+async fn raw_config_and_save_follow_state_rules() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    let rx = t.motor.send_raw_line("config_udc 24").await.unwrap();
+    assert_eq!(t.motor.wait_raw_reply(rx, "config_udc", WAIT_TIMEOUT).await.unwrap(), "config_udc ok");
+    assert!(t.motor.unsaved.load(Relaxed));
+    let rx = t.motor.send_raw_line("save").await.unwrap();
+    t.motor.wait_raw_reply(rx, "save done", WAIT_TIMEOUT).await.unwrap();
+    assert!(!t.motor.unsaved.load(Relaxed));
+
+    t.motor.send_running_command(&MotorRunCommand::SetSpeed(10.0)).await.unwrap();
+    for line in ["config_udc 24", "save"] {
+        let result = t.motor.send_raw_line(line).await;
+        assert!(matches!(result, Err(MotorError::InvalidState { state: MotorState::DebugRun, .. })), "{result:?}");
+    }
+    let result = t.motor.send_raw_line("bootloader").await;
+    assert!(matches!(result, Err(MotorError::InvalidArgument(_))), "{result:?}");
+    wait_until("set_speed to arrive", || t.firmware.received().len() == 3).await;
+    assert_eq!(t.firmware.received(), ["config_udc 24", "save", "set_speed 10"]);
+}
+
+#[tokio::test]
+async fn over_limit_current_feedback_stops_motor() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    // 假固件上报的相电流峰值为 0.5
+    *t.motor.safety.lock().await = SafetyLimits { max_current: Some(0.4), ..Default::default() };
+    t.motor.send_running_command(&MotorRunCommand::SetSpeed(10.0)).await.unwrap();
+    t.motor.set_feedback(MotorFeedbackState::Current).await.unwrap();
+
+    let event = t.wait_event("safety violation", |e| matches!(e, MotorEvent::SafetyViolation(_))).await;
+    let MotorEvent::SafetyViolation(violation) = event else { unreachable!() };
+    assert_eq!((violation.kind, violation.action), (SafetyKind::Current, SafetyAction::Stopped));
+    assert_eq!(t.motor.state(), MotorState::Stop);
+    wait_until("stop to arrive", || t.firmware.received().last().is_some_and(|c| c == "stop")).await;
+}
+
+#[tokio::test]
+async fn sync_state_follows_firmware() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.query_firmware().await.unwrap();
+    t.motor.send_running_command(&MotorRunCommand::SetSpeed(10.0)).await.unwrap();
+    assert_eq!(t.motor.sync_state().await.unwrap(), MotorState::DebugRun);
+
+    // 下位机自行停机，如 keepalive 超时
+    t.firmware.set_state(MotorState::Stop);
+    assert_eq!(t.motor.sync_state().await.unwrap(), MotorState::Stop);
+    assert_eq!(t.motor.state(), MotorState::Stop);
+    t.wait_event("state change", |e| matches!(e, MotorEvent::StateChanged(MotorState::Stop))).await;
+}
+
+#[tokio::test]
+async fn sync_state_keeps_local_fault() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.query_firmware().await.unwrap();
+    t.motor.send_running_command(&MotorRunCommand::SetSpeed(10.0)).await.unwrap();
+    t.firmware.send_line("fault: overcurrent");
+    t.wait_event("fault", |e| matches!(e, MotorEvent::Fault(_))).await;
+
+    // 下位机已自行停机，故障仍需用户确认
+    t.firmware.set_state(MotorState::Stop);
+    assert_eq!(t.motor.sync_state().await.unwrap(), MotorState::Fault);
+    assert_eq!(t.motor.state(), MotorState::Fault);
+    t.motor.clear_fault().await.unwrap();
+    assert_eq!(t.motor.sync_state().await.unwrap(), MotorState::Stop);
+}
+
+#[tokio::test]
+async fn binary_protocol_carries_commands_and_feedback() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.query_firmware().await.unwrap();
+    assert_eq!(t.motor.negotiate_protocol().await.unwrap(), Protocol::Binary);
+    assert_eq!(t.motor.protocol(), Protocol::Binary);
+
+    let mut rx = t.motor.feedback_tx.subscribe();
+    t.motor.send_running_command(&MotorRunCommand::SetPosition(1.5)).await.unwrap();
+    assert_eq!(next_feedback(&mut rx, |_| true).await, FeedbackValue::Position(1.5));
+    // 文本应答同样经过帧传输
+    let config = t.motor.load_config().await.unwrap();
+    assert_eq!(config.id, 3);
+    assert_eq!(t.firmware.received(), ["get_version", "protocol binary", "set_position 1.5", "get_config"]);
+    assert_eq!(t.motor.serial.stats.framing.corrupted_frames.load(Relaxed), 0);
+}
+
+#[tokio::test]
+async fn restore_text_protocol_before_disconnect() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.query_firmware().await.unwrap();
+    t.motor.negotiate_protocol().await.unwrap();
+    t.motor.restore_text_protocol().await.unwrap();
+
+    assert_eq!(t.motor.protocol(), Protocol::Text);
+    assert_eq!(t.motor.load_config().await.unwrap().id, 3);
+    assert_eq!(t.firmware.received(), ["get_version", "protocol binary", "protocol text", "get_config"]);
+}
+
+#[tokio::test]
+async fn stale_binary_protocol_is_reset_on_connect() {
+    let t = TestMotor::connect(FirmwareScript { binary: true, ..Default::default() }).await;
+    t.motor.serial.reset_remote_protocol().await.unwrap();
+    let info = t.motor.query_firmware().await.unwrap();
+
+    assert_eq!(info.version, "1.4.0");
+    assert_eq!(t.motor.protocol(), Protocol::Text);
+    assert_eq!(t.firmware.received(), ["protocol text", "get_version"]);
+}
+
+#[tokio::test]
+async fn protocol_reset_is_harmless_in_text_mode() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.serial.reset_remote_protocol().await.unwrap();
+
+    assert_eq!(t.motor.query_firmware().await.unwrap().version, "1.4.0");
+    assert_eq!(t.firmware.received(), ["get_version"]);
+}
+
+fn sweep_request() -> SweepRequest {
+    SweepRequest {
+        target: SweepLoop::Speed,
+        grid: SweepGrid { kp: vec![0.5, 1.0, 1.5], ki: vec![0.01], kd: vec![] },
+        output_max: None,
+        step: 10.0,
+        duration_ms: 2000,
+        rest_ms: 0,
+        settle_band: 0.02,
+        sort_by: None,
+    }
+}
+
+#[tokio::test]
+async fn gain_sweep_aborts_when_stopped() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.query_firmware().await.unwrap();
+    let motor = t.motor.clone();
+    let sweep = tokio::spawn(async move { run_gain_sweep(&motor, &sweep_request()).await });
+    wait_until("first step", || t.firmware.received().iter().any(|c| c.starts_with("set_speed"))).await;
+    t.motor.send_running_command(&MotorRunCommand::Stop).await.unwrap();
+
+    let result = timeout(WAIT_TIMEOUT, sweep).await.expect("sweep did not abort").unwrap();
+    assert!(matches!(result, Err(MotorError::InvalidState { state: MotorState::Stop, .. })), "{result:?}");
+    let received = t.firmware.received();
+    assert_eq!(received.iter().filter(|c| c.starts_with("set_speed")).count(), 1);
+    // 原始参数已恢复
+    assert_eq!(received.last().unwrap(), "config_speed_pi 0.8 0.01 10");
+    assert!(!t.motor.unsaved.load(Relaxed));
+}
+
+#[tokio::test]
+async fn gain_sweep_reports_fault_before_restore_failure() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.query_firmware().await.unwrap();
+    let motor = t.motor.clone();
+    let sweep = tokio::spawn(async move { run_gain_sweep(&motor, &sweep_request()).await });
+    wait_until("first step", || t.firmware.received().iter().any(|c| c.starts_with("set_speed"))).await;
+    t.firmware.send_line("fault: overcurrent");
+
+    // 故障状态下无法恢复参数，返回的仍是故障
+    let result = timeout(WAIT_TIMEOUT, sweep).await.expect("sweep did not abort").unwrap();
+    assert!(matches!(result, Err(MotorError::FaultDetected(_))), "{result:?}");
+    assert_eq!(t.motor.state(), MotorState::Fault);
+    assert!(t.motor.unsaved.load(Relaxed));
+}
+
+#[tokio::test]
+async fn unplugged_port_reports_disconnect() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.send_running_command(&MotorRunCommand::SetSpeed(10.0)).await.unwrap();
+    t.firmware.unplug();
+
+    t.wait_event("disconnect", |e| matches!(e, MotorEvent::Disconnected)).await;
+    assert!(!t.motor.serial.connected.load(Relaxed));
+    let result = t.motor.send_running_command(&MotorRunCommand::Stop).await;
+    assert!(matches!(result, Err(MotorError::SerialError(_))), "{result:?}");
+    assert!(!t.events().iter().any(|e| matches!(e, MotorEvent::ConnectionLost)));
+}
+
+#[tokio::test]
+async fn unplugged_port_with_auto_reconnect_reports_connection_lost() {
+    let t = TestMotor::connect(FirmwareScript::default()).await;
+    t.motor.auto_reconnect.store(true, Relaxed);
+    t.firmware.unplug();
+
+    t.wait_event("connection lost", |e| matches!(e, MotorEvent::ConnectionLost)).await;
+    assert!(!t.events().iter().any(|e| matches!(e, MotorEvent::Disconnected)));
+}
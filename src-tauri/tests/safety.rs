@@ -0,0 +1,104 @@
+//! 安全限制的检查
+
+use ipmesctool_lib::feedback_parser::FeedbackValue;
+use ipmesctool_lib::safety::{SafetyAction, SafetyKind, SafetyLimits};
+use ipmesctool_lib::waveform::SetpointTarget;
+use tokio::time::Duration;
+
+#[test]
+fn non_finite_setpoint_is_rejected_before_limits() {
+    // 没有任何限制时同样拒绝
+    let limits = SafetyLimits::default();
+    for value in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+        for target in [SetpointTarget::Speed, SetpointTarget::Position] {
+            let violation = limits.check_setpoint(target, value, None).unwrap_err();
+            assert_eq!(violation.kind, SafetyKind::NotFinite);
+        }
+    }
+    let limits = SafetyLimits { max_slew_rate: Some(10.0), ..Default::default() };
+    let violation = limits.check_setpoint(SetpointTarget::Speed, f32::NAN, Some((0.0, Duration::from_secs(1)))).unwrap_err();
+    assert_eq!(violation.kind, SafetyKind::NotFinite);
+    assert!(limits.check_setpoint(SetpointTarget::Speed, 5.0, Some((0.0, Duration::from_secs(1)))).is_ok());
+}
+
+#[test]
+fn invalid_limits_are_rejected() {
+    let invalid = [
+        SafetyLimits { max_speed: Some(-1.0), ..Default::default() },
+        SafetyLimits { max_speed: Some(f32::NAN), ..Default::default() },
+        SafetyLimits { max_current: Some(f32::INFINITY), ..Default::default() },
+        SafetyLimits { max_slew_rate: Some(-0.5), ..Default::default() },
+        SafetyLimits { min_position: Some(f32::NEG_INFINITY), ..Default::default() },
+        SafetyLimits { min_position: Some(2.0), max_position: Some(1.0), ..Default::default() },
+    ];
+    for limits in invalid {
+        assert!(limits.validate().is_err(), "{limits:?}");
+    }
+    let valid = SafetyLimits { max_speed: Some(100.0), min_position: Some(-3.0), max_position: Some(3.0), max_current: Some(0.0), ..Default::default() };
+    assert!(valid.validate().is_ok());
+    assert!(SafetyLimits::default().validate().is_ok());
+}
+
+#[test]
+fn over_limit_speed_is_rejected() {
+    let limits = SafetyLimits { max_speed: Some(100.0), ..Default::default() };
+    for value in [100.5, -100.5] {
+        let violation = limits.check_setpoint(SetpointTarget::Speed, value, None).unwrap_err();
+        assert_eq!((violation.kind, violation.value, violation.limit), (SafetyKind::Speed, value, 100.0));
+        assert_eq!(violation.action, SafetyAction::Rejected);
+    }
+    assert!(limits.check_setpoint(SetpointTarget::Speed, -100.0, None).is_ok());
+    // 速度限制不作用于位置
+    assert!(limits.check_setpoint(SetpointTarget::Position, 1000.0, None).is_ok());
+}
+
+#[test]
+fn position_outside_window_is_rejected() {
+    let limits = SafetyLimits { min_position: Some(-1.0), max_position: Some(2.0), ..Default::default() };
+    for (value, limit) in [(-1.5, -1.0), (2.5, 2.0)] {
+        let violation = limits.check_setpoint(SetpointTarget::Position, value, None).unwrap_err();
+        assert_eq!((violation.kind, violation.limit), (SafetyKind::Position, limit));
+    }
+    for value in [-1.0, 0.0, 2.0] {
+        assert!(limits.check_setpoint(SetpointTarget::Position, value, None).is_ok());
+    }
+    // 只设置一侧时另一侧不限制
+    let limits = SafetyLimits { max_position: Some(2.0), ..Default::default() };
+    assert!(limits.check_setpoint(SetpointTarget::Position, -100.0, None).is_ok());
+}
+
+#[test]
+fn slew_rate_is_limited() {
+    let limits = SafetyLimits { max_slew_rate: Some(10.0), ..Default::default() };
+    let last = Some((0.0, Duration::from_millis(100)));
+    // 0.1s 内变化 2，即 20/s
+    let violation = limits.check_setpoint(SetpointTarget::Speed, 2.0, last).unwrap_err();
+    assert_eq!((violation.kind, violation.limit), (SafetyKind::SlewRate, 10.0));
+    assert!((violation.value - 20.0).abs() < 1e-3, "{violation:?}");
+    assert!(limits.check_setpoint(SetpointTarget::Speed, -0.5, last).is_ok());
+    // 没有上一次设定值时不检查
+    assert!(limits.check_setpoint(SetpointTarget::Speed, 50.0, None).is_ok());
+}
+
+#[test]
+fn over_limit_feedback_requests_stop() {
+    let limits = SafetyLimits {
+        max_speed: Some(100.0),
+        min_position: Some(-1.0),
+        max_position: Some(1.0),
+        max_current: Some(5.0),
+        ..Default::default()
+    };
+    let cases = [
+        (FeedbackValue::Current(0.5, -6.0, 1.0), SafetyKind::Current, 6.0),
+        (FeedbackValue::Speed(-120.0), SafetyKind::Speed, -120.0),
+        (FeedbackValue::Position(1.5), SafetyKind::Position, 1.5),
+    ];
+    for (value, kind, reported) in cases {
+        let violation = limits.check_feedback(&value).unwrap();
+        assert_eq!((violation.kind, violation.value, violation.action), (kind, reported, SafetyAction::Stopped));
+    }
+    assert!(limits.check_feedback(&FeedbackValue::Current(4.0, -5.0, 1.0)).is_none());
+    // 母线电压没有安全限制
+    assert!(limits.check_feedback(&FeedbackValue::Udc(1000.0)).is_none());
+}
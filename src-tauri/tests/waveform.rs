@@ -0,0 +1,36 @@
+//! 波形参数校验
+
+use ipmesctool_lib::waveform::Waveform;
+
+#[test]
+fn non_finite_parameters_are_rejected() {
+    for bad in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+        let invalid = [
+            Waveform::Sine { amplitude: 1.0, frequency: bad, offset: 0.0 },
+            Waveform::Square { amplitude: bad, frequency: 1.0, offset: 0.0 },
+            Waveform::Triangle { amplitude: 1.0, frequency: 1.0, offset: bad },
+            Waveform::Trapezoid { low: 0.0, high: bad, ramp_time: 1.0, hold_time: 1.0 },
+            Waveform::Trapezoid { low: 0.0, high: 1.0, ramp_time: bad, hold_time: 1.0 },
+            Waveform::Ramp { start: 0.0, end: 1.0, slope: bad },
+            Waveform::Ramp { start: bad, end: 1.0, slope: 1.0 },
+        ];
+        for waveform in invalid {
+            assert!(waveform.validate().is_err(), "{waveform:?}");
+        }
+    }
+}
+
+#[test]
+fn timing_must_be_positive() {
+    let invalid = [
+        Waveform::Sine { amplitude: 1.0, frequency: 0.0, offset: 0.0 },
+        Waveform::Trapezoid { low: 0.0, high: 1.0, ramp_time: 0.0, hold_time: 0.0 },
+        Waveform::Trapezoid { low: 0.0, high: 1.0, ramp_time: -1.0, hold_time: 2.0 },
+        Waveform::Ramp { start: 0.0, end: 1.0, slope: -1.0 },
+    ];
+    for waveform in invalid {
+        assert!(waveform.validate().is_err(), "{waveform:?}");
+    }
+    assert!(Waveform::Sine { amplitude: 1.0, frequency: 2.0, offset: -1.0 }.validate().is_ok());
+    assert!(Waveform::Trapezoid { low: 0.0, high: 1.0, ramp_time: 0.0, hold_time: 1.0 }.validate().is_ok());
+}
@@ -21,6 +21,8 @@ const DeviceInfo = React.lazy(() => import("@/pages/device-info.tsx"));
 export const LazyPages = {
   "Debug.Serial": SerialConsole,
   "Debug.Chart": React.lazy(() => import("@/pages/chart.tsx")),
+  "Debug.DeviceLog": React.lazy(() => import("@/pages/device-log.tsx")),
+  "Debug.Script": React.lazy(() => import("@/pages/bench-script.tsx")),
   "Motor.PID": PidConfig,
   "Motor.Encoder": React.lazy(() => import("@/pages/encoder-config.tsx")),
   "Motor.Calibration": React.lazy(() => import("@/pages/calibration.tsx")),
@@ -18,6 +18,8 @@ import { useMemo } from "react";
 import RefreshConfigButton from "@/components/refresh-config-button.tsx";
 import { motorConnectedAtom } from "@/stores/motor.ts";
 import SaveConfigButton from "@/components/save-config-button.tsx";
+import EmergencyStopButton from "@/components/emergency-stop-button.tsx";
+import LinkHealth from "@/components/link-health.tsx";
 
 export default function AppSidebar() {
   const [page, setPage] = useAtom(pageAtom);
@@ -50,11 +52,13 @@ export default function AppSidebar() {
         <SidebarFooter>
           {connected && (
             <>
+              <EmergencyStopButton />
               <SaveConfigButton />
               <RefreshConfigButton />
             </>
           )}
           <MotorState />
+          {connected && <LinkHealth />}
           <Device />
         </SidebarFooter>
       </Sidebar>
@@ -12,6 +12,7 @@ import {
 import { Button } from "@/components/ui/button.tsx";
 import { ArrowDownLeft } from "lucide-react";
 import { toast } from "sonner";
+import { formatError } from "@/motor.ts";
 import { invoke } from "@tauri-apps/api/core";
 import { useDegAtom, useRpmAtom } from "@/stores/angle.ts";
 import { deg2rad, rpm2rps } from "@/lib/utils.ts";
@@ -35,7 +36,7 @@ export default function DebugRunConsole() {
         });
         setRunState("Speed");
       } catch (e) {
-        toast.error(`Error: ${e}`);
+        toast.error(`Error: ${formatError(e)}`);
       }
     },
     [setRunState, useRpm],
@@ -49,7 +50,7 @@ export default function DebugRunConsole() {
         });
         setRunState("Position");
       } catch (e) {
-        toast.error(`Error: ${e}`);
+        toast.error(`Error: ${formatError(e)}`);
       }
     },
     [setRunState, useDeg],
@@ -60,7 +61,7 @@ export default function DebugRunConsole() {
       await invoke("motor_stop");
       setRunState("Stop");
     } catch (e) {
-      toast.error(`Error: ${e}`);
+      toast.error(`Error: ${formatError(e)}`);
     }
   }, [setRunState]);
 
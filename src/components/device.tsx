@@ -17,8 +17,14 @@ import {
   SelectValue,
 } from "@/components/ui/select.tsx";
 import { Button } from "@/components/ui/button.tsx";
-import { Plug, Unplug } from "lucide-react";
-import { MotorConfig } from "@/motor.ts";
+import { Plug, RefreshCw, Search, Unplug } from "lucide-react";
+import {
+  formatError,
+  MotorConfig,
+  ProbeResult,
+  ReconnectPolicy,
+  SerialPortDescriptor,
+} from "@/motor.ts";
 import { Spinner } from "@/components/ui/spinner.tsx";
 import { toast } from "sonner";
 
@@ -30,17 +36,28 @@ export async function disconnect() {
   }
 }
 
+function portLabel(port: SerialPortDescriptor) {
+  const label = port.product
+    ? `${port.port_name} (${port.product})`
+    : port.port_name;
+  if (port.is_esc) return `${label} · ESC`;
+  return port.usb_uart ? `${label} · USB 串口` : label;
+}
+
 export default function Device({
   className,
   ...props
 }: React.HTMLAttributes<HTMLDivElement>) {
   const [connected, setConnected] = useAtom(motorConnectedAtom);
   const [connecting, setConnecting] = useState<boolean>(false);
-  const [portList, setPortList] = useState<string[]>([]);
+  const [portList, setPortList] = useState<SerialPortDescriptor[]>([]);
   const setMotorConfig = useSetAtom(motorConfigAtom);
   const setMotorState = useSetAtom(motorStateAtom);
 
   const [selected, setSelected] = useState<string>("");
+  // 自动重连
+  const [autoReconnect, setAutoReconnect] = useState<boolean>(false);
+  const [reconnecting, setReconnecting] = useState<boolean>(false);
 
   const getConfigOrDisconnect = useCallback(async () => {
     try {
@@ -49,7 +66,7 @@ export default function Device({
       setConnected(true);
     } catch (e) {
       console.log(e);
-      toast.error(`get config failed!\n${e}`);
+      toast.error(`get config failed!\n${formatError(e)}`);
       // 获取配置失败，不是我们的设备，关闭连接
       await disconnect();
       setConnecting(false);
@@ -60,27 +77,80 @@ export default function Device({
     const md = listen("motor-disconnected", () => {
       setConnected(false);
       setConnecting(false);
+      setReconnecting(false);
       disconnect().then();
     });
-    invoke("list_serial_ports").then((ports) => {
-      setPortList(ports as string[]);
+    const mcl = listen("motor-connection-lost", () => {
+      setReconnecting(true);
+      toast.warning("连接中断，正在等待设备重新连接");
     });
-    const spc = listen<string[]>("serial-port-changed", (event) => {
-      const ports = event.payload as string[];
+    const mr = listen<string>("motor-reconnected", (event) => {
+      setReconnecting(false);
+      setSelected(event.payload);
+      // 下位机重新上电后处于 Stop 状态
+      setMotorState("Stop");
+      getConfigOrDisconnect().then();
+      toast.success(`已重新连接 ${event.payload}`);
+    });
+    invoke<ReconnectPolicy>("get_reconnect_policy").then((policy) => {
+      setAutoReconnect(policy.enabled);
+    });
+    invoke<SerialPortDescriptor[]>("list_serial_ports").then((ports) => {
       setPortList(ports);
     });
+    const spc = listen<SerialPortDescriptor[]>(
+      "serial-port-changed",
+      (event) => {
+        setPortList(event.payload);
+      },
+    );
 
     return () => {
       md.then((unlisten) => unlisten());
+      mcl.then((unlisten) => unlisten());
+      mr.then((unlisten) => unlisten());
       spc.then((unlisten) => unlisten());
     };
-  }, [setConnected, setConnecting, setPortList]);
+  }, [
+    setConnected,
+    setConnecting,
+    setPortList,
+    setMotorState,
+    getConfigOrDisconnect,
+  ]);
 
   useEffect(() => {
-    if (selected && !portList.includes(selected)) {
+    if (selected && !portList.some((p) => p.port_name === selected)) {
       setSelected("");
     }
-  }, [portList, selected, setSelected]);
+    // 未选择时自动选中识别为电调的串口
+    if (!selected && !connected && !connecting) {
+      const esc = portList.find((p) => p.is_esc);
+      if (esc) setSelected(esc.port_name);
+    }
+  }, [portList, selected, setSelected, connected, connecting]);
+
+  // 心跳，DebugRun 期间界面卡死时后端会自动停机。
+  // 窗口已关闭后台降频（tauri.conf.json 的 backgroundThrottling），但 Windows 和 Linux 不支持该配置，
+  // 隐藏时定时器仍可能被降频到超过 HOST_TIMEOUT，因此隐藏期间暂停监视，重新显示后恢复
+  useEffect(() => {
+    if (!connected) return;
+    const beat = () => {
+      invoke("motor_heartbeat", { visible: !document.hidden }).catch(
+        console.error,
+      );
+    };
+    const timer = setInterval(beat, 500);
+    document.addEventListener("visibilitychange", beat);
+    const wt = listen("watchdog-tripped", () => {
+      toast.warning("界面心跳超时，电机已停止");
+    });
+    return () => {
+      clearInterval(timer);
+      document.removeEventListener("visibilitychange", beat);
+      wt.then((unlisten) => unlisten());
+    };
+  }, [connected]);
 
   const effectOnceRef = useRef(false);
   useEffect(() => {
@@ -100,16 +170,66 @@ export default function Device({
   const connect = useCallback(async () => {
     setConnecting(true);
     try {
-      await invoke("connect_motor", { portName: selected, baudRate: 115200 });
+      // 先探测电调使用的波特率
+      const probe: ProbeResult = await invoke("probe_serial_port", {
+        portName: selected,
+      });
+      if (probe.baud_rate === null) {
+        toast.error(
+          `未检测到电调，已尝试波特率: ${probe.tried.join(", ")}`,
+        );
+        setConnecting(false);
+        return;
+      }
+      await invoke("connect_motor", {
+        portName: selected,
+        baudRate: probe.baud_rate,
+      });
       await getConfigOrDisconnect();
       // 默认 Stop
       setMotorState("Stop");
     } catch (e) {
       console.error(e);
-      toast.error(`connect error!\n${e}`);
+      toast.error(`connect error!\n${formatError(e)}`);
     }
     setConnecting(false);
   }, [getConfigOrDisconnect, selected, setMotorState]);
+
+  // 没有能直接识别的电调时，由用户手动发起探测 USB 串口，应答配置的才选中；
+  // 探测期间视为连接中，避免与用户连接同时占用串口
+  const findEsc = useCallback(async () => {
+    setConnecting(true);
+    try {
+      for (const port of portList.filter((p) => p.usb_uart)) {
+        try {
+          const r = await invoke<ProbeResult>("probe_serial_port", {
+            portName: port.port_name,
+          });
+          if (r.config) {
+            setSelected(port.port_name);
+            return;
+          }
+        } catch (e) {
+          console.debug(e);
+        }
+      }
+      toast.error("未在 USB 串口上找到电调");
+    } finally {
+      setConnecting(false);
+    }
+  }, [portList]);
+
+  const toggleAutoReconnect = useCallback(async () => {
+    const policy: ReconnectPolicy = await invoke("get_reconnect_policy");
+    try {
+      await invoke("set_reconnect_policy", {
+        policy: { ...policy, enabled: !autoReconnect },
+      });
+      setAutoReconnect(!autoReconnect);
+    } catch (e) {
+      toast.error(`设置自动重连失败: ${formatError(e)}`);
+    }
+  }, [autoReconnect]);
   return (
     <div
       className={cn(
@@ -125,7 +245,7 @@ export default function Device({
           size="icon"
           onClick={() => disconnect()}
         >
-          <Unplug />
+          {reconnecting ? <Spinner /> : <Unplug />}
         </Button>
       ) : (
         <Button
@@ -151,8 +271,8 @@ export default function Device({
             <SelectContent>
               <SelectGroup>
                 {portList.map((port) => (
-                  <SelectItem key={port} value={port}>
-                    {port}
+                  <SelectItem key={port.port_name} value={port.port_name}>
+                    {portLabel(port)}
                   </SelectItem>
                 ))}
               </SelectGroup>
@@ -170,6 +290,27 @@ export default function Device({
           </Select>
         )}
       </div>
+      <Button
+        className="border-0 rounded-none border-l"
+        variant="ghost"
+        size="icon"
+        title="在 USB 串口上查找电调"
+        onClick={() => findEsc()}
+        disabled={
+          connected || connecting || !portList.some((p) => p.usb_uart)
+        }
+      >
+        <Search />
+      </Button>
+      <Button
+        className="border-0 rounded-none border-l"
+        variant={autoReconnect ? "secondary" : "ghost"}
+        size="icon"
+        title="自动重连"
+        onClick={() => toggleAutoReconnect()}
+      >
+        <RefreshCw className={autoReconnect ? "" : "opacity-40"} />
+      </Button>
     </div>
   );
 }
@@ -0,0 +1,75 @@
+import { Button } from "@/components/ui/button.tsx";
+import { useCallback, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import { useAtom } from "jotai";
+import { OctagonX } from "lucide-react";
+import { EmergencyStopReport, formatError } from "@/motor.ts";
+import { emergencyStoppedAtom } from "@/stores/motor.ts";
+
+/**
+ * 急停按钮，快捷键 Ctrl/Cmd+Shift+Space 在窗口不在前台时同样有效
+ */
+export default function EmergencyStopButton() {
+  const [stopped, setStopped] = useAtom(emergencyStoppedAtom);
+
+  useEffect(() => {
+    invoke<boolean>("is_motor_emergency_stopped").then(setStopped);
+
+    // 快捷键触发的急停也会发出该事件
+    const l = listen<EmergencyStopReport>("emergency-stop", (event) => {
+      setStopped(true);
+      // 未能查询到状态（固件不支持或超时）同样视为未确认
+      const failed = event.payload.devices.filter(
+        (d) =>
+          !d.sent ||
+          d.state === null ||
+          d.state === "Run" ||
+          d.state === "DebugRun",
+      );
+      if (failed.length > 0) {
+        toast.error(
+          `急停未确认: ${failed.map((d) => `${d.port_name} ${d.error ?? d.state ?? "状态未知"}`).join(", ")}`,
+        );
+      } else {
+        toast.warning("已急停，解除后才能再次运行");
+      }
+    });
+    return () => {
+      l.then((unlisten) => unlisten());
+    };
+  }, [setStopped]);
+
+  const release = useCallback(async () => {
+    try {
+      await invoke("clear_motor_emergency_stop");
+      setStopped(false);
+    } catch (e) {
+      toast.error(`解除急停失败: ${formatError(e)}`);
+    }
+  }, [setStopped]);
+
+  return (
+    <div className="flex gap-2">
+      <Button
+        className="flex-1"
+        variant="destructive"
+        title="Ctrl/Cmd+Shift+Space"
+        onClick={() =>
+          invoke("motor_emergency_stop").catch((e) =>
+            toast.error(`急停失败: ${formatError(e)}`),
+          )
+        }
+      >
+        <OctagonX />
+        急停
+      </Button>
+      {stopped && (
+        <Button variant="outline" onClick={release}>
+          解除急停
+        </Button>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,84 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { cn } from "@/lib/utils";
+import {
+  Tooltip,
+  TooltipContent,
+  TooltipTrigger,
+} from "@/components/ui/tooltip.tsx";
+import { LinkReport } from "@/motor.ts";
+
+const healthLabel = {
+  Good: "正常",
+  Degraded: "不稳定",
+  Down: "已断开",
+};
+
+/**
+ * 串口链路状况指示，数据由后端每秒上报
+ */
+export default function LinkHealth() {
+  const [report, setReport] = useState<LinkReport | null>(null);
+
+  useEffect(() => {
+    invoke<LinkReport>("get_link_stats")
+      .then(setReport)
+      .catch(() => setReport(null));
+    const l = listen<LinkReport>("link-stats", (event) => {
+      setReport(event.payload);
+    });
+    return () => {
+      l.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  if (!report) return null;
+  const { totals, rates, latency } = report;
+  const errors =
+    totals.parse_failures +
+    totals.corrupted_frames +
+    totals.overflows +
+    totals.invalid_utf8 +
+    totals.lagged;
+
+  return (
+    <Tooltip>
+      <TooltipTrigger asChild>
+        <div className="w-full border rounded-md px-3 py-2 flex items-center text-sm">
+          <span className="text-muted-foreground w-20">Link</span>
+          <span
+            className={cn(
+              "size-2 rounded-full mr-2",
+              report.health === "Good" && "bg-green-500",
+              report.health === "Degraded" && "bg-amber-500",
+              report.health === "Down" && "bg-destructive",
+            )}
+          />
+          <span className="font-medium">{healthLabel[report.health]}</span>
+          <span className="ml-auto text-muted-foreground">
+            {rates.feedback.toFixed(0)} fb/s
+          </span>
+        </div>
+      </TooltipTrigger>
+      <TooltipContent side="right" className="flex flex-col gap-0.5">
+        <span>
+          收 {rates.bytes_in.toFixed(0)} B/s · 发 {rates.bytes_out.toFixed(0)}{" "}
+          B/s
+        </span>
+        <span>
+          行 {rates.lines.toFixed(1)}/s · 指令 {rates.commands.toFixed(1)}/s
+        </span>
+        <span>
+          应答延迟 {latency.last_ms.toFixed(1)} ms (平均{" "}
+          {latency.avg_ms.toFixed(1)}, 最大 {latency.max_ms.toFixed(1)})
+        </span>
+        <span>
+          错误 {errors}：解析 {totals.parse_failures} · 坏帧{" "}
+          {totals.corrupted_frames} · 超长 {totals.overflows} · 编码{" "}
+          {totals.invalid_utf8} · 丢弃 {totals.lagged}
+        </span>
+      </TooltipContent>
+    </Tooltip>
+  );
+}
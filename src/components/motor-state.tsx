@@ -10,6 +10,9 @@ import { useEffect } from "react";
 import { listen } from "@tauri-apps/api/event";
 import { invoke } from "@tauri-apps/api/core";
 import { cn } from "@/lib/utils";
+import { Button } from "@/components/ui/button.tsx";
+import { toast } from "sonner";
+import { FaultRecord, formatError, SafetyViolation } from "@/motor.ts";
 
 export function MotorState() {
   const [motorState, setMotorState] = useAtom(motorStateAtom);
@@ -29,9 +32,25 @@ export function MotorState() {
     const l = listen("motor-state-change", (event) => {
       setMotorState(event.payload as MotorStateType);
     });
+    const f = listen<FaultRecord>("motor-fault", (event) => {
+      const fault = event.payload;
+      toast.error(`电机故障 ${fault.code}: ${fault.description}`);
+    });
+
+    const sv = listen<SafetyViolation>("safety-violation", (event) => {
+      const v = event.payload;
+      const action = v.action === "Stopped" ? "已自动停机" : "指令已拒绝";
+      if (v.kind === "NotFinite") {
+        toast.error(`设定值不是有效数字，${action}`);
+      } else {
+        toast.error(`超出安全限制 ${v.kind}: ${v.value} (限制 ${v.limit})，${action}`);
+      }
+    });
 
     return () => {
       l.then((unlisten) => unlisten());
+      f.then((unlisten) => unlisten());
+      sv.then((unlisten) => unlisten());
     };
   }, [setMotorState, setUnsaved]);
 
@@ -59,7 +78,7 @@ export function MotorState() {
           </span>
         </div>
       </div>
-      <div className="w-full border rounded-md px-3 py-2 flex">
+      <div className="w-full border rounded-md px-3 py-2 flex items-center">
         <span className="text-muted-foreground w-20">State</span>
         <span
           className={cn(
@@ -72,6 +91,20 @@ export function MotorState() {
         >
           {motorState}
         </span>
+        {motorState === "Fault" && (
+          <Button
+            className="ml-auto h-6"
+            size="sm"
+            variant="destructive"
+            onClick={() =>
+              invoke("motor_clear_fault").catch((e) =>
+                toast.error(`清除故障失败: ${formatError(e)}`),
+              )
+            }
+          >
+            清除故障
+          </Button>
+        )}
       </div>
     </div>
   ) : (
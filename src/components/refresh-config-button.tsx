@@ -5,7 +5,7 @@ import { useSetAtom } from "jotai";
 import { motorConfigAtom } from "@/stores/motor.ts";
 import { toast } from "sonner";
 import { invoke } from "@tauri-apps/api/core";
-import { MotorConfig } from "@/motor.ts";
+import { formatError, MotorConfig } from "@/motor.ts";
 
 export default function RefreshConfigButton() {
   const [refreshing, setRefreshing] = useState(false);
@@ -18,7 +18,7 @@ export default function RefreshConfigButton() {
       toast.success(`Motor Config Refreshed!`);
     } catch (e) {
       console.log(e);
-      toast.error(`刷新失败 e: ${e}`);
+      toast.error(`刷新失败 e: ${formatError(e)}`);
     }
     setRefreshing(false);
   }, [setConfig]);
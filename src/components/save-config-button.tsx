@@ -3,6 +3,7 @@ import { Spinner } from "@/components/ui/spinner.tsx";
 import { useCallback, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { toast } from "sonner";
+import { formatError } from "@/motor.ts";
 import { useAtom } from "jotai";
 import { motorConfigUnsavedAtom } from "@/stores/motor.ts";
 
@@ -17,7 +18,7 @@ export default function SaveConfigButton() {
       toast.success(`Motor Config Saved!`);
     } catch (e) {
       console.log(e);
-      toast.error(`保存失败 e: ${e}`);
+      toast.error(`保存失败 e: ${formatError(e)}`);
     }
     setSaving(false);
   }, [setUnsaved]);
@@ -71,3 +71,177 @@ const motorConfig = z.object({
 });
 
 export type MotorConfig = z.infer<typeof motorConfig>;
+
+export interface SerialPortDescriptor {
+  port_name: string;
+  port_type: "usb" | "pci" | "bluetooth" | "unknown";
+  vid: number | null;
+  pid: number | null;
+  serial_number: string | null;
+  manufacturer: string | null;
+  product: string | null;
+  // 产品名或序列号表明是电调
+  is_esc: boolean;
+  // 常见的 USB 转串口芯片，只作提示
+  usb_uart: boolean;
+}
+
+export interface SafetyLimits {
+  max_speed: number | null;
+  min_position: number | null;
+  max_position: number | null;
+  max_slew_rate: number | null;
+  max_current: number | null;
+}
+
+export interface SafetyViolation {
+  kind: "Speed" | "Position" | "SlewRate" | "Current" | "NotFinite";
+  // NaN、无穷大序列化为 null
+  value: number | null;
+  limit: number;
+  action: "Rejected" | "Stopped";
+}
+
+export interface FaultRecord {
+  timestamp: number;
+  code:
+    | "Overcurrent"
+    | "Overvoltage"
+    | "Undervoltage"
+    | "EncoderError"
+    | "OverTemperature"
+    | "Unknown";
+  description: string;
+  raw: string;
+  previous_state: MotorState;
+}
+
+export interface FirmwareInfo {
+  version: string;
+  build: string;
+  commands: string[];
+  encoder_types: string[];
+  legacy: boolean;
+}
+
+export interface ProbeResult {
+  port_name: string;
+  baud_rate: number | null;
+  config: MotorConfig | null;
+  tried: number[];
+}
+
+export interface ReconnectPolicy {
+  enabled: boolean;
+  timeout_secs: number;
+}
+
+export type Protocol = "Text" | "Binary";
+
+export interface LinkReport {
+  health: "Good" | "Degraded" | "Down";
+  totals: {
+    bytes_in: number;
+    bytes_out: number;
+    commands_sent: number;
+    lines_received: number;
+    feedback_received: number;
+    parse_failures: number;
+    corrupted_frames: number;
+    overflows: number;
+    invalid_utf8: number;
+    lagged: number;
+  };
+  rates: {
+    bytes_in: number;
+    bytes_out: number;
+    commands: number;
+    lines: number;
+    feedback: number;
+    errors: number;
+  };
+  latency: {
+    count: number;
+    last_ms: number;
+    avg_ms: number;
+    max_ms: number;
+  };
+}
+
+export interface DeviceStopResult {
+  port_name: string;
+  sent: boolean;
+  error: string | null;
+  state: MotorState | null;
+}
+
+export interface EmergencyStopReport {
+  devices: DeviceStopResult[];
+}
+
+export type FlashStage =
+  | "EnterBootloader"
+  | "Erase"
+  | "Write"
+  | "Verify"
+  | "Reboot"
+  | "Done";
+
+export interface FlashProgress {
+  stage: FlashStage;
+  written: number;
+  total: number;
+}
+
+export interface ScriptAssertion {
+  message: string;
+  passed: boolean;
+}
+
+export interface ScriptReport {
+  passed: boolean;
+  assertions: ScriptAssertion[];
+  logs: string[];
+  error: string | null;
+  duration_ms: number;
+}
+
+const errorCategory = z.enum([
+  "NotConnected",
+  "InvalidState",
+  "InvalidArgument",
+  "Timeout",
+  "DeviceRejected",
+  "Parse",
+  "Io",
+]);
+
+export type ErrorCategory = z.infer<typeof errorCategory>;
+
+/**
+ * 与 rust 端 MotorError 的序列化结构一致
+ */
+const motorError = z.object({
+  code: z.string(),
+  category: errorCategory,
+  message: z.string(),
+  context: z.unknown(),
+});
+
+export type MotorError = z.infer<typeof motorError>;
+
+export function parseMotorError(e: unknown): MotorError | undefined {
+  const result = motorError.safeParse(e);
+  return result.success ? result.data : undefined;
+}
+
+/**
+ * 将 invoke 返回的错误转换为可读的文本
+ */
+export function formatError(e: unknown): string {
+  const error = parseMotorError(e);
+  if (error) {
+    return `[${error.code}] ${error.message}`;
+  }
+  return e instanceof Error ? e.message : String(e);
+}
@@ -0,0 +1,104 @@
+import { useCallback, useEffect, useRef, useState } from "react";
+import { Button } from "@/components/ui/button.tsx";
+import { Textarea } from "@/components/ui/textarea.tsx";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import { formatError, ScriptReport } from "@/motor.ts";
+
+const EXAMPLE = `set_speed(50);
+assert(wait_feedback("speed", ">=", 45, 2000), "speed reaches 45");
+stop();`;
+
+export default function BenchScript() {
+  const [source, setSource] = useState(EXAMPLE);
+  const [running, setRunning] = useState(false);
+  const [logs, setLogs] = useState<string[]>([]);
+  const [report, setReport] = useState<ScriptReport | null>(null);
+  const scrollRef = useRef<HTMLDivElement>(null);
+
+  // 脚本运行中的 log/assert 输出
+  useEffect(() => {
+    const un = listen<string>("script-log", (event) => {
+      setLogs((prev) => [...prev, event.payload]);
+    });
+    return () => {
+      un.then((f) => f());
+    };
+  }, []);
+
+  useEffect(() => {
+    scrollRef.current?.scrollTo({ top: scrollRef.current.scrollHeight });
+  }, [logs]);
+
+  const run = useCallback(async () => {
+    setRunning(true);
+    setLogs([]);
+    setReport(null);
+    try {
+      const r = await invoke<ScriptReport>("run_bench_script", { source });
+      setReport(r);
+      if (r.passed) {
+        toast.success("脚本通过");
+      } else {
+        toast.error("脚本未通过");
+      }
+    } catch (e) {
+      toast.error(`运行失败: ${formatError(e)}`);
+    }
+    setRunning(false);
+  }, [source]);
+
+  const abort = useCallback(async () => {
+    try {
+      await invoke("abort_bench_script");
+    } catch (e) {
+      toast.error(`中止失败: ${formatError(e)}`);
+    }
+  }, []);
+
+  return (
+    <div className="w-full flex flex-col h-full border rounded-lg p-4 gap-3">
+      <Textarea
+        className="font-mono min-h-48"
+        value={source}
+        onChange={(e) => setSource(e.target.value)}
+        disabled={running}
+      />
+
+      <div className="flex flex-row gap-2">
+        <Button onClick={() => run()} disabled={running || !source.trim()}>
+          运行
+        </Button>
+        <Button variant="destructive" onClick={() => abort()} disabled={!running}>
+          中止
+        </Button>
+        {report && (
+          <span
+            className={`self-center font-bold ${report.passed ? "text-green-600" : "text-red-600"}`}
+          >
+            {report.passed ? "PASS" : "FAIL"}（
+            {report.assertions.filter((a) => a.passed).length}/
+            {report.assertions.length}，{report.duration_ms} ms）
+          </span>
+        )}
+      </div>
+
+      {report?.error && (
+        <div className="text-red-600 font-mono text-sm whitespace-pre-wrap">
+          {report.error}
+        </div>
+      )}
+
+      {/* 脚本输出 */}
+      <div
+        ref={scrollRef}
+        className="flex-1 overflow-y-auto bg-neutral-950 text-neutral-100 rounded-md p-3 font-mono text-sm whitespace-pre-wrap"
+      >
+        {logs.map((log, index) => (
+          <div key={index}>{log}</div>
+        ))}
+      </div>
+    </div>
+  );
+}
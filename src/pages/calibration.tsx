@@ -17,7 +17,7 @@ import {
   CardHeader,
   CardTitle,
 } from "@/components/ui/card.tsx";
-import { MotorConfig } from "@/motor.ts";
+import { formatError, MotorConfig } from "@/motor.ts";
 
 export default function Calibration() {
   const connected = useAtomValue(motorConnectedAtom);
@@ -93,6 +93,19 @@ export default function Calibration() {
           >
             {calibrating && <Spinner className="mr-2" />}开始校准
           </Button>
+          {calibrating && (
+            <Button
+              className="ml-2"
+              variant="outline"
+              onClick={() =>
+                invoke("motor_stop").catch((e) =>
+                  toast.error(`中止校准失败: ${formatError(e)}`),
+                )
+              }
+            >
+              中止校准
+            </Button>
+          )}
         </CardFooter>
       </Card>
     </div>
@@ -8,6 +8,7 @@ import { atom, useAtom } from "jotai";
 import { Button } from "@/components/ui/button.tsx";
 import { Label } from "@/components/ui/label.tsx";
 import { toast } from "sonner";
+import { formatError } from "@/motor.ts";
 import { invoke } from "@tauri-apps/api/core";
 import { WaveformControlPanel } from "@/components/plot/waveform-control-panel.tsx";
 import { WaveformContainer } from "@/components/plot/waveform-container.tsx";
@@ -75,7 +76,7 @@ export default function Chart() {
                   await invoke("set_motor_feedback", { feedback: state });
                   setState(state);
                 } catch (e) {
-                  toast.error(`设置反馈类型失败: ${e}`);
+                  toast.error(`设置反馈类型失败: ${formatError(e)}`);
                 }
               }}
             >
@@ -15,7 +15,16 @@ import { Button } from "@/components/ui/button.tsx";
 import { RefreshCcw, Save } from "lucide-react";
 import { setPartValue } from "@/lib/utils.ts";
 import { toast } from "sonner";
+import {
+  FirmwareInfo,
+  FlashProgress,
+  FlashStage,
+  formatError,
+  Protocol,
+  SafetyLimits,
+} from "@/motor.ts";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import {
   AlertDialog,
   AlertDialogAction,
@@ -146,9 +155,162 @@ export function IdInput({
   );
 }
 
+const safetyFields: { key: keyof SafetyLimits; label: string }[] = [
+  { key: "max_speed", label: "最大速度" },
+  { key: "min_position", label: "最小位置" },
+  { key: "max_position", label: "最大位置" },
+  { key: "max_slew_rate", label: "最大变化率/s" },
+  { key: "max_current", label: "最大电流" },
+];
+
+/**
+ * 软件安全限制，仅对当前连接有效，0 表示不限制
+ */
+function SafetyLimitsCard() {
+  const [limits, setLimits] = useState<SafetyLimits | null>(null);
+
+  useEffect(() => {
+    invoke<SafetyLimits>("get_safety_limits")
+      .then(setLimits)
+      .catch(() => setLimits(null));
+  }, []);
+
+  if (!limits) return null;
+
+  const update = async (key: keyof SafetyLimits, v: number) => {
+    const next = { ...limits, [key]: v === 0 ? null : v };
+    try {
+      await invoke("set_safety_limits", { limits: next });
+      setLimits(next);
+    } catch (e) {
+      toast.error(`安全限制设置失败: ${formatError(e)}`);
+    }
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="text-base">安全限制</CardTitle>
+        <CardAction></CardAction>
+      </CardHeader>
+      <CardContent className="text-sm flex flex-col gap-2">
+        {safetyFields.map(({ key, label }) => (
+          <InputLine
+            key={key}
+            label={label}
+            value={limits[key] ?? 0}
+            onChange={(v) => update(key, v)}
+          />
+        ))}
+      </CardContent>
+    </Card>
+  );
+}
+
+const flashStageNames: Record<FlashStage, string> = {
+  EnterBootloader: "进入 bootloader",
+  Erase: "擦除",
+  Write: "写入",
+  Verify: "校验",
+  Reboot: "重启",
+  Done: "完成",
+};
+
+/**
+ * 固件烧录，支持 .hex 与 .bin，完成后设备重启并断开连接
+ */
+function FirmwareUpdateCard() {
+  const [path, setPath] = useState("");
+  const [flashing, setFlashing] = useState(false);
+  const [progress, setProgress] = useState<FlashProgress | null>(null);
+  const [confirmOpen, setConfirmOpen] = useState(false);
+
+  useEffect(() => {
+    const un = listen<FlashProgress>("flash-progress", (event) =>
+      setProgress(event.payload),
+    );
+    return () => {
+      un.then((f) => f());
+    };
+  }, []);
+
+  const flash = async () => {
+    setFlashing(true);
+    setProgress(null);
+    try {
+      await invoke("flash_motor_firmware", { path: path.trim() });
+      toast.success("烧录完成，设备重启后请重新连接");
+    } catch (e) {
+      toast.error(`烧录失败: ${formatError(e)}`);
+    }
+    setFlashing(false);
+  };
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="text-base">固件更新</CardTitle>
+        <CardAction></CardAction>
+      </CardHeader>
+      <CardContent className="text-sm flex flex-col gap-2">
+        <div className="flex flex-row gap-2">
+          <Input
+            className="flex-1 font-mono"
+            placeholder="固件路径（.hex / .bin）"
+            value={path}
+            disabled={flashing}
+            onChange={(e) => setPath(e.target.value)}
+          />
+          <Button
+            disabled={flashing || !path.trim()}
+            onClick={() => setConfirmOpen(true)}
+          >
+            烧录
+          </Button>
+        </div>
+        {progress && (
+          <span className="text-muted-foreground">
+            {flashStageNames[progress.stage]} {progress.written}/
+            {progress.total} 字节
+          </span>
+        )}
+      </CardContent>
+
+      <AlertDialog open={confirmOpen} onOpenChange={setConfirmOpen}>
+        <AlertDialogContent>
+          <AlertDialogHeader>
+            <AlertDialogTitle>确认烧录？</AlertDialogTitle>
+            <AlertDialogDescription>
+              烧录过程中请勿断开电源或串口，完成后设备会重启并断开连接。
+            </AlertDialogDescription>
+          </AlertDialogHeader>
+          <AlertDialogFooter>
+            <AlertDialogCancel>取消</AlertDialogCancel>
+            <AlertDialogAction onClick={() => flash()}>
+              开始烧录
+            </AlertDialogAction>
+          </AlertDialogFooter>
+        </AlertDialogContent>
+      </AlertDialog>
+    </Card>
+  );
+}
+
 export default function DeviceInfo() {
   const [config, setConfig] = useAtom(motorConfigAtom);
   const setUnsaved = useSetAtom(motorConfigUnsavedAtom);
+  const [firmware, setFirmware] = useState<FirmwareInfo | null>(null);
+  const [protocol, setProtocol] = useState<Protocol | null>(null);
+
+  useEffect(() => {
+    if (!config) return;
+    invoke<FirmwareInfo>("get_firmware_info")
+      .then(setFirmware)
+      .catch(() => setFirmware(null));
+    invoke<Protocol>("get_motor_protocol")
+      .then(setProtocol)
+      .catch(() => setProtocol(null));
+  }, [config]);
 
   return config ? (
     <div className="w-full p-4 space-y-4">
@@ -158,6 +320,24 @@ export default function DeviceInfo() {
           <CardAction></CardAction>
         </CardHeader>
         <CardContent className="text-sm flex flex-col gap-2">
+          {firmware && (
+            <div className="flex flex-row items-center">
+              <Label className="w-28">固件版本</Label>
+              <span className="text-muted-foreground">
+                {firmware.legacy
+                  ? "旧版固件（不支持版本查询）"
+                  : `${firmware.version} (${firmware.build})`}
+              </span>
+            </div>
+          )}
+          {protocol && (
+            <div className="flex flex-row items-center">
+              <Label className="w-28">通信协议</Label>
+              <span className="text-muted-foreground">
+                {protocol === "Binary" ? "二进制帧 (COBS + CRC16)" : "文本"}
+              </span>
+            </div>
+          )}
           <IdInput
             value={config.id}
             onChange={async (v) => {
@@ -166,7 +346,7 @@ export default function DeviceInfo() {
                 setUnsaved(true);
                 setPartValue(setConfig, config, "id", v);
               } catch (e) {
-                toast.error(`id 设置失败: ${e}`);
+                toast.error(`id 设置失败: ${formatError(e)}`);
               }
             }}
           />
@@ -187,7 +367,7 @@ export default function DeviceInfo() {
                 setUnsaved(true);
                 setPartValue(setConfig, config, "udc", v);
               } catch (e) {
-                toast.error(`Udc 设置失败: ${e}`);
+                toast.error(`Udc 设置失败: ${formatError(e)}`);
               }
             }}
           />
@@ -208,12 +388,14 @@ export default function DeviceInfo() {
                 setUnsaved(true);
                 setPartValue(setConfig, config, "fc", v);
               } catch (e) {
-                toast.error(`Idq Filter Fc 设置失败: ${e}`);
+                toast.error(`Idq Filter Fc 设置失败: ${formatError(e)}`);
               }
             }}
           />
         </CardContent>
       </Card>
+      <SafetyLimitsCard />
+      <FirmwareUpdateCard />
     </div>
   ) : (
     <div>设备未连接</div>
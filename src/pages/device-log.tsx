@@ -0,0 +1,60 @@
+import { useEffect, useRef, useState } from "react";
+import { useAtom } from "jotai";
+import { Button } from "@/components/ui/button.tsx";
+import { DeviceLog, deviceLogAtom } from "@/stores/serial.ts";
+
+const levelStyles: Record<DeviceLog["level"], string> = {
+  Info: "text-neutral-200",
+  Warn: "bg-yellow-900/50 text-yellow-200",
+  Error: "bg-red-900/50 text-red-200",
+};
+
+export default function DeviceLogPage() {
+  const [logs, setLogs] = useAtom(deviceLogAtom);
+  const [onlyProblems, setOnlyProblems] = useState(false);
+  const scrollRef = useRef<HTMLDivElement>(null);
+
+  const shown = onlyProblems ? logs.filter((l) => l.level !== "Info") : logs;
+
+  // 自动滚动到底部
+  useEffect(() => {
+    scrollRef.current?.scrollTo({
+      top: scrollRef.current.scrollHeight,
+      behavior: "smooth",
+    });
+  }, [shown.length]);
+
+  return (
+    <div className="w-full flex flex-col h-full border rounded-lg p-4 gap-3">
+      <div
+        ref={scrollRef}
+        className="flex-1 overflow-y-auto bg-neutral-950 rounded-md p-3 space-y-1"
+      >
+        {shown.map((log, index) => (
+          <div
+            key={index}
+            className={`rounded-md px-2 py-1 text-sm font-mono whitespace-pre-wrap ${levelStyles[log.level]}`}
+          >
+            <span className="opacity-60 mr-2">
+              {new Date(log.timestamp).toLocaleTimeString()}
+            </span>
+            <span className="font-bold mr-2">{log.level}</span>
+            {log.message}
+          </div>
+        ))}
+      </div>
+
+      <div className="flex justify-end gap-2">
+        <Button
+          variant="outline"
+          onClick={() => setOnlyProblems(!onlyProblems)}
+        >
+          {onlyProblems ? "显示全部" : "只看告警与错误"}
+        </Button>
+        <Button variant="secondary" onClick={() => setLogs([])}>
+          清空
+        </Button>
+      </div>
+    </div>
+  );
+}
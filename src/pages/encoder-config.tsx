@@ -3,6 +3,7 @@ import {
   EncoderConfig as EncoderConfigType,
   EncoderDirection,
   EncoderType,
+  formatError,
 } from "@/motor.ts";
 import React, { useCallback, useEffect } from "react";
 import { motorConfigAtom, motorConfigUnsavedAtom } from "@/stores/motor.ts";
@@ -101,7 +102,7 @@ export default function EncoderConfig() {
                   setUnsaved(true);
                 } catch (e) {
                   console.log(e);
-                  toast.error(`保存失败, e: ${e}`);
+                  toast.error(`保存失败, e: ${formatError(e)}`);
                 }
               }}
             >
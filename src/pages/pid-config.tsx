@@ -29,6 +29,7 @@ import { Key, RefreshCcw, Save } from "lucide-react";
 import { setPartValue } from "@/lib/utils.ts";
 import { invoke } from "@tauri-apps/api/core";
 import { toast } from "sonner";
+import { formatError } from "@/motor.ts";
 import { useSetAtom } from "jotai/index";
 
 const speedPIAtom = atom<SpeedPI | null>(null);
@@ -277,7 +278,7 @@ export default function PidConfig() {
             setUnsaved(true);
           } catch (e) {
             console.log(e);
-            toast.error(`保存失败, e: ${e}`);
+            toast.error(`保存失败, e: ${formatError(e)}`);
           }
         }}
       />
@@ -299,7 +300,7 @@ export default function PidConfig() {
             setUnsaved(true);
           } catch (e) {
             console.log(e);
-            toast.error(`保存失败, e: ${e}`);
+            toast.error(`保存失败, e: ${formatError(e)}`);
           }
         }}
       />
@@ -351,7 +352,7 @@ export default function PidConfig() {
             setUnsaved(true);
           } catch (e) {
             console.log(e);
-            toast.error(`保存失败, e: ${e}`);
+            toast.error(`保存失败, e: ${formatError(e)}`);
           }
         }}
       />
@@ -1,11 +1,62 @@
-import { useEffect, useRef } from "react";
+import React, { useCallback, useEffect, useRef, useState } from "react";
 import { Button } from "@/components/ui/button.tsx";
+import { Input } from "@/components/ui/input.tsx";
 import { useAtom } from "jotai";
 import { serialDataAtom } from "@/stores/serial.ts";
+import { invoke } from "@tauri-apps/api/core";
+import { toast } from "sonner";
+import { formatError } from "@/motor.ts";
 
 export default function SerialConsole() {
   const [serialData, setSerialData] = useAtom(serialDataAtom);
   const scrollRef = useRef<HTMLDivElement>(null);
+  const [line, setLine] = useState("");
+  const [waitFor, setWaitFor] = useState("");
+  const [sending, setSending] = useState(false);
+  const [history, setHistory] = useState<string[]>([]);
+  // 浏览历史时的位置，等于 history.length 表示正在输入新指令
+  const [historyIndex, setHistoryIndex] = useState(0);
+
+  useEffect(() => {
+    invoke<string[]>("get_raw_history").then((h) => {
+      setHistory(h);
+      setHistoryIndex(h.length);
+    });
+  }, []);
+
+  const send = useCallback(async () => {
+    if (!line.trim() || sending) return;
+    setSending(true);
+    try {
+      const reply = await invoke<string | null>("send_raw_line", {
+        line,
+        waitFor: waitFor || null,
+        timeoutMs: 2000,
+      });
+      if (reply !== null) toast.success(reply);
+      setLine("");
+    } catch (e) {
+      toast.error(`发送失败: ${formatError(e)}`);
+    }
+    const h = await invoke<string[]>("get_raw_history");
+    setHistory(h);
+    setHistoryIndex(h.length);
+    setSending(false);
+  }, [line, waitFor, sending]);
+
+  const onKeyDown = (e: React.KeyboardEvent<HTMLInputElement>) => {
+    if (e.key === "Enter") {
+      send().then();
+    } else if (e.key === "ArrowUp" && historyIndex > 0) {
+      e.preventDefault();
+      setHistoryIndex(historyIndex - 1);
+      setLine(history[historyIndex - 1]);
+    } else if (e.key === "ArrowDown" && historyIndex < history.length) {
+      e.preventDefault();
+      setHistoryIndex(historyIndex + 1);
+      setLine(history[historyIndex + 1] ?? "");
+    }
+  };
 
   // 自动滚动到底部
   useEffect(() => {
@@ -48,6 +99,26 @@ export default function SerialConsole() {
         ))}
       </div>
 
+      {/* 发送原始指令，↑/↓ 浏览历史 */}
+      <div className="flex flex-row gap-2">
+        <Input
+          className="flex-1 font-mono"
+          placeholder="输入指令，回车发送"
+          value={line}
+          onChange={(e) => setLine(e.target.value)}
+          onKeyDown={onKeyDown}
+        />
+        <Input
+          className="w-40 font-mono"
+          placeholder="等待应答（可选）"
+          value={waitFor}
+          onChange={(e) => setWaitFor(e.target.value)}
+        />
+        <Button onClick={() => send()} disabled={!line.trim() || sending}>
+          发送
+        </Button>
+      </div>
+
       {/* 顶部工具条 */}
       <div className="flex justify-end">
         <Button variant="secondary" onClick={() => setSerialData([])}>
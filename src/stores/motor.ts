@@ -14,3 +14,5 @@ export const motorDebugRunStateAtom = atom<MotorDebugRunState>("Stop");
 export const motorConfigAtom = atom<MotorConfig>();
 
 export const motorConfigUnsavedAtom = atom<boolean>(false);
+
+export const emergencyStoppedAtom = atom<boolean>(false);
@@ -6,7 +6,9 @@ export type PageID =
   | "Motor.Encoder"
   | "Motor.Calibration"
   | "Debug.Chart"
-  | "Debug.Serial";
+  | "Debug.Serial"
+  | "Debug.DeviceLog"
+  | "Debug.Script";
 
 export interface PageGroup {
   name: string;
@@ -60,6 +62,14 @@ export const PageGroups: PageGroup[] = [
         name: "串口数据",
         id: "Debug.Serial",
       },
+      {
+        name: "设备日志",
+        id: "Debug.DeviceLog",
+      },
+      {
+        name: "测试脚本",
+        id: "Debug.Script",
+      },
     ],
   },
 ];
@@ -2,6 +2,7 @@ import { listen } from "@tauri-apps/api/event";
 import { atom } from "jotai";
 import { useAtom } from "jotai/index";
 import { useEffect } from "react";
+import { toast } from "sonner";
 
 export interface SerialData {
   type: "rx" | "tx";
@@ -10,8 +11,19 @@ export interface SerialData {
 }
 export const serialDataAtom = atom<SerialData[]>([]);
 
+export interface DeviceLog {
+  timestamp: number;
+  level: "Info" | "Warn" | "Error";
+  message: string;
+}
+
+const MAX_DEVICE_LOG = 1000;
+
+export const deviceLogAtom = atom<DeviceLog[]>([]);
+
 export function useSerialDebug() {
   const [, setSerialData] = useAtom(serialDataAtom);
+  const [, setDeviceLog] = useAtom(deviceLogAtom);
   // 监听串口事件（只读）
   useEffect(() => {
     const lr = listen("serial-received", (event) => {
@@ -28,9 +40,19 @@ export function useSerialDebug() {
       ]);
     });
 
+    // 下位机的告警、故障等消息，反馈期间也会收到
+    const ll = listen<DeviceLog>("device-log", (event) => {
+      const log = event.payload;
+      setDeviceLog((prev) => [...prev, log].slice(-MAX_DEVICE_LOG));
+      if (log.level === "Error") {
+        toast.error(log.message);
+      }
+    });
+
     return () => {
       lr.then((un) => un());
       lt.then((un) => un());
+      ll.then((un) => un());
     };
-  }, [setSerialData]);
+  }, [setSerialData, setDeviceLog]);
 }